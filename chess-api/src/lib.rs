@@ -1,3 +1,6 @@
+// `sabi_trait` generates impls inside of a const block
+#![allow(non_local_definitions)]
+
 use std::path::Path;
 
 pub use abi_stable;
@@ -329,6 +332,9 @@ impl<'a> TimeoutReference<'a> {
 pub trait StableTimeout {
     #[must_use]
     fn is_complete(&self) -> bool;
+
+    #[must_use]
+    fn stop_after_iteration(&self, depth: u16, result: EvaluatedMove) -> bool;
 }
 
 impl<T: ?Sized + Timeout> StableTimeout for T {
@@ -336,6 +342,11 @@ impl<T: ?Sized + Timeout> StableTimeout for T {
     fn is_complete(&self) -> bool {
         Timeout::is_complete(self)
     }
+
+    #[inline]
+    fn stop_after_iteration(&self, depth: u16, result: EvaluatedMove) -> bool {
+        Timeout::stop_after_iteration(self, depth, result.chess_move(), result.score())
+    }
 }
 
 impl Timeout for TimeoutReference<'_> {
@@ -343,4 +354,10 @@ impl Timeout for TimeoutReference<'_> {
     fn is_complete(&self) -> bool {
         self.ptr.is_complete()
     }
+
+    #[inline]
+    fn stop_after_iteration(&self, depth: u16, best_move: Option<ChessMove>, score: Score) -> bool {
+        self.ptr
+            .stop_after_iteration(depth, EvaluatedMove::new(best_move, score))
+    }
 }
@@ -135,6 +135,9 @@ impl BitBoard {
         Some(pos)
     }
 
+    /// # Safety
+    ///
+    /// The bitboard must not be empty
     #[inline(always)]
     pub unsafe fn pop_unchecked(&mut self) -> Pos {
         let pos = unsafe { NonZeroU64::new_unchecked(self.0) };
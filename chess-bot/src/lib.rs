@@ -1,50 +1,67 @@
 use abi_stable::prefix_type::PrefixTypeTrait;
 use chess_api::{abi_stable::export_root_module, Board, MoveResult};
+use chess_movegen::Game;
 
 #[export_root_module]
 fn load_api() -> chess_api::ChessApiRefRaw {
-    PrefixTypeTrait::leak_into_prefix(chess_api::ChessApi::new(|| ChessBot {
-        three_fold: chess_engine::ThreeFold::new(),
-        board: Board::standard(),
-        engine: chess_engine::Engine::default(),
+    PrefixTypeTrait::leak_into_prefix(chess_api::ChessApi::new(|| {
+        let mut engine = chess_engine::Engine::default();
+        engine.config = chess_engine::SearchConfig {
+            null_move_pruning: cfg!(feature = "null-move"),
+            late_move_reductions: cfg!(feature = "late-move-reductions"),
+            futility_pruning: cfg!(feature = "futility-pruning"),
+            check_extensions: cfg!(feature = "check-extensions"),
+        };
+
+        // set by `make-bot --params`, to ship bots with differently tuned evaluations
+        if let Some(params) = option_env!("CHESS_BOT_PARAMS") {
+            engine.set_params(
+                chess_engine::EvalParams::from_toml(params).expect("CHESS_BOT_PARAMS is invalid"),
+            );
+        }
+
+        // set by `make-bot --nnue`, the network is loaded when the bot starts
+        if let Some(path) = option_env!("CHESS_BOT_NNUE").filter(|path| !path.is_empty()) {
+            engine.set_network(Some(
+                chess_engine::Network::load(path)
+                    .unwrap_or_else(|err| panic!("failed to load {path}: {err}")),
+            ));
+        }
+
+        ChessBot {
+            game: Game::default(),
+            engine,
+        }
     }))
 }
 
 pub struct ChessBot {
-    three_fold: chess_engine::ThreeFold,
     engine: chess_engine::Engine,
-    board: Board,
+    game: Game,
 }
 
 impl chess_api::ChessEngineTrait for ChessBot {
     fn evaluate(&mut self, timeout: chess_api::TimeoutReference<'_>) -> chess_api::EvaluatedMove {
-        let (mv, score) = self.engine.search(&self.board, &self.three_fold, &timeout);
+        let three_fold = chess_engine::ThreeFold::from(&self.game);
+        let (mv, score) = self.engine.search(self.game.board(), &three_fold, &timeout);
         chess_api::EvaluatedMove::new(mv, score)
     }
 
     fn set_board(&mut self, board: Board) {
-        self.board = board;
-        self.three_fold = chess_engine::ThreeFold::new();
+        self.game = Game::new(board);
     }
 
     fn make_move(&mut self, mv: chess_api::StableChessMove) -> MoveResult {
         let mv = chess_movegen::ChessMove::from(mv);
-        let is_valid = self.board.move_mut(mv);
-
-        if !is_valid {
-            return MoveResult {
-                is_valid: false,
-                is_three_fold_draw: false,
-            };
-        }
+        let is_valid = self.game.push(mv);
 
         MoveResult {
-            is_valid: true,
-            is_three_fold_draw: self.three_fold.add(self.board),
+            is_valid,
+            is_three_fold_draw: is_valid && self.game.repetitions() >= 3,
         }
     }
 
     fn board(&self) -> Board {
-        self.board
+        *self.game.board()
     }
 }
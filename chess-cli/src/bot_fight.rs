@@ -1,4 +1,4 @@
-use chess_movegen::Board;
+use chess_movegen::{Board, DrawReason, Game, Outcome};
 use colorz::Colorize;
 use rand::seq::SliceRandom;
 use std::{collections::HashMap, path::PathBuf, time::Duration};
@@ -21,7 +21,7 @@ pub struct Args {
 #[derive(Debug)]
 enum GameResult {
     CheckMate { winner: usize, loser: usize },
-    StaleMate { x: usize, y: usize },
+    Draw { x: usize, y: usize, reason: DrawReason },
     DidntMove { bot_id: usize, opp_id: usize },
 }
 
@@ -86,10 +86,10 @@ pub fn main(args: Args) {
 
             a.set_board(Board::standard());
             b.set_board(Board::standard());
-            let mut moves = Vec::new();
+            let mut game = Game::default();
 
             let result = loop {
-                let (bot, bot_id, opp_id) = match a.board().turn() {
+                let (bot, bot_id, opp_id) = match game.board().turn() {
                     chess_bitboard::Color::White => (&mut a,x,y),
                     chess_bitboard::Color::Black => (&mut b,y,x),
                 };
@@ -97,13 +97,17 @@ pub fn main(args: Args) {
                 let (mv, _score) = bot.evaluate(&timeout);
                 
                 if let Some(mv) = mv {
-                    moves.push(mv);
-                    let res = a.make_move(mv);
-                    b.make_move(mv);
-
-                    if res.is_three_fold_draw {
-                        break GameResult::StaleMate { x: bot_id, y: opp_id };
+                    if !game.push(mv) {
+                        tracing::error!(
+                            bot=?args.bots[bot_id],
+                            ?time_control,
+                            "{} {}", "made an illegal move".red(), mv.uci()
+                        );
+                        break GameResult::DidntMove { bot_id, opp_id };
                     }
+
+                    a.make_move(mv);
+                    b.make_move(mv);
                 } else {
                     tracing::error!(
                         bot=?args.bots[bot_id],
@@ -113,14 +117,24 @@ pub fn main(args: Args) {
                     break GameResult::DidntMove { bot_id, opp_id };
                 }
 
-                match a.board().state() {
-                    chess_movegen::GameState::CheckMate => break GameResult::CheckMate { winner: bot_id, loser:opp_id },
-                     chess_movegen::GameState::StaleMate => break GameResult::StaleMate { x: bot_id, y: opp_id },
-                    chess_movegen::GameState::Check | chess_movegen::GameState::Running => (),
+                match game.outcome() {
+                    Some(Outcome::Checkmate { .. }) => break GameResult::CheckMate { winner: bot_id, loser: opp_id },
+                    Some(Outcome::Draw(reason)) => break GameResult::Draw { x: bot_id, y: opp_id, reason },
+                    None => (),
                 }
             };
             let end = start.elapsed();
 
+            tracing::debug!(
+                "moves between {} ({x}) and {} ({y}): {}",
+                args.bots[x].display(),
+                args.bots[y].display(),
+                game.moves()
+                    .iter()
+                    .map(|mv| mv.uci().to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
 
             tracing::info!(
                 x,
@@ -128,11 +142,11 @@ pub fn main(args: Args) {
                 x.path=?args.bots[x].display(),
                 y.path=?args.bots[y].display(),
                 duratin=?end,
-                moves.len=moves.len(),
+                moves.len=game.moves().len(),
                 "completed game between {} ({x}) and {} ({y}) at {time_control:?} per move after {} moves as a {result:?} in {end:?}",
                 args.bots[x].display(),
                 args.bots[y].display(),
-                moves.len()
+                game.moves().len()
             );
 
             let game_id = if x < y {
@@ -162,7 +176,7 @@ pub fn main(args: Args) {
                         *y_win += 1;
                     }
                 },
-                GameResult::StaleMate { .. } => *ties += 1,
+                GameResult::Draw { .. } => *ties += 1,
                 GameResult::DidntMove { .. } => (),
             }
 
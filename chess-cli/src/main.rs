@@ -1,13 +1,15 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use chess_engine::{DurationTimeout, Engine, ThreeFold};
-use chess_movegen::Board;
+use chess_engine::{Clock, DurationTimeout, Engine, SearchInfo, ThreeFold, TimeManager};
+use chess_movegen::{Board, Game, Outcome};
 
 use rand::Rng;
 
 mod bot_fight;
 mod logs;
 mod make_bot;
+mod perft;
+mod tune;
 
 #[derive(clap::Parser)]
 struct Args {
@@ -21,9 +23,22 @@ struct Args {
 
 #[derive(Clone, clap::Parser)]
 enum ArgKind {
-    OnBoard { board: Option<Board> },
+    OnBoard {
+        board: Option<Board>,
+        /// The number of threads to search with
+        #[clap(long, default_value_t = 1)]
+        threads: usize,
+        /// Give both sides this much time for the whole game, instead of 5s per move
+        #[clap(long, value_parser(parse_duration::parse))]
+        clock: Option<Duration>,
+        /// Time added to a side's clock after each of its moves
+        #[clap(long, value_parser(parse_duration::parse), default_value = "0s")]
+        increment: Duration,
+    },
     BotFight(bot_fight::Args),
     MakeBot(make_bot::Args),
+    Tune(tune::Args),
+    Perft(perft::Args),
 }
 
 fn main() {
@@ -34,16 +49,24 @@ fn main() {
     match args.kind {
         ArgKind::BotFight(args) => bot_fight::main(args),
         ArgKind::MakeBot(args) => make_bot::main(args),
-        ArgKind::OnBoard { board } => {
+        ArgKind::Tune(args) => tune::main(args),
+        ArgKind::Perft(args) => perft::main(args),
+        ArgKind::OnBoard {
+            board,
+            threads,
+            clock,
+            increment,
+        } => {
             let mut engine = Engine::default();
-            let mut three_fold = ThreeFold::new();
+            engine.threads = threads;
             let mut book_moves = if board.is_none() {
                 chess_lookup::INITIAL_BOOOK_MOVES
             } else {
                 chess_lookup::EMPTY_BOOK_MOVES
             };
 
-            let mut board = board.unwrap_or_else(Board::standard);
+            let mut game = Game::new(board.unwrap_or_else(Board::standard));
+            let mut clocks = clock.map(|clock| [clock; 2]);
 
             loop {
                 let x = book_moves.into_iter().count();
@@ -55,8 +78,8 @@ fn main() {
                     .sample(rand::distributions::WeightedIndex::new((1..=x).rev()).unwrap());
                 let mv = book_moves.into_iter().nth(x).unwrap();
 
-                eprintln!("{board:?}");
-                assert!(board.move_mut(chess_movegen::ChessMove {
+                eprintln!("{:?}", game.board());
+                assert!(game.push(chess_movegen::ChessMove {
                     source: mv.source,
                     dest: mv.dest,
                     piece: None,
@@ -65,43 +88,72 @@ fn main() {
             }
 
             loop {
+                let board = *game.board();
+                let three_fold = ThreeFold::from(&game);
                 eprintln!("{board}");
                 eprintln!("{board:?}");
 
-                // let start = std::time::Instant::now();
-                let (mv, score) = engine.search(
-                    &board,
-                    &three_fold,
-                    DurationTimeout::new(Duration::from_millis(5000)),
-                );
+                let turn = board.turn();
+                let start = Instant::now();
+                let (mv, score) = match clocks {
+                    Some(clocks) => {
+                        let clock = Clock {
+                            remaining: clocks[turn],
+                            increment,
+                            moves_to_go: None,
+                        };
+                        let time = TimeManager::new(clock, turn);
+                        engine.search_observed(&board, &three_fold, &time, print_info)
+                    }
+                    None => engine.search_observed(
+                        &board,
+                        &three_fold,
+                        DurationTimeout::new(Duration::from_millis(5000)),
+                        print_info,
+                    ),
+                };
+
+                if let Some(clocks) = &mut clocks {
+                    let Some(remaining) = clocks[turn].checked_sub(start.elapsed()) else {
+                        println!("LOSS ({turn:?} ran out of time)");
+                        break;
+                    };
+                    clocks[turn] = remaining + increment;
+                }
 
                 let Some(mv) = mv else {
                     println!("DRAW (MATERIAL)");
                     break;
                 };
-                // dbg!(start.elapsed());
-                assert!(board.move_mut(mv));
+                assert!(game.push(mv));
                 eprintln!(
                     "{score:?} {mv} moves: {}, max_depth: {}",
                     engine.moves_evaluated, engine.max_depth
                 );
 
-                if three_fold.add(board) {
-                    println!("DRAW (THREE FOLD)");
-                    break;
-                }
-
-                if board.legals().is_empty() {
-                    if board.in_check() {
-                        println!("WIN");
-                    } else {
-                        println!("DRAW (NO LEGAL MOVES)");
-                    }
-                    break;
+                match game.outcome() {
+                    Some(Outcome::Checkmate { .. }) => println!("WIN"),
+                    Some(Outcome::Draw(reason)) => println!("DRAW ({reason})"),
+                    None => continue,
                 }
+                break;
             }
 
-            eprintln!("{board:?}");
+            eprintln!("{:?}", game.board());
         }
     }
 }
+
+fn print_info(info: &SearchInfo) {
+    let pv = info.pv.iter().map(|mv| mv.to_string()).collect::<Vec<_>>();
+    eprintln!(
+        "depth {}/{} {:?} nodes {} nps {} time {:?} pv {}",
+        info.depth,
+        info.seldepth,
+        info.score,
+        info.nodes,
+        info.nps(),
+        info.elapsed,
+        pv.join(" "),
+    );
+}
@@ -1,16 +1,58 @@
 use std::path::PathBuf;
 
+use chess_engine::EvalParams;
+
 #[derive(Clone, clap::Parser)]
 pub struct Args {
     name: String,
     #[clap(long)]
     strip: bool,
+    /// Build the bot without the default features, which enable each part of the selective search
+    #[clap(long)]
+    no_default_features: bool,
+    /// Features of chess-bot to enable, e.g. `null-move`
+    #[clap(long, value_delimiter = ',')]
+    features: Vec<String>,
+    /// Evaluation weights to build the bot with, from a `.toml` or `.json` file
+    #[clap(long)]
+    params: Option<PathBuf>,
+    /// Evaluate with this network instead of the classical evaluation, the bot loads it
+    /// from this path when it starts
+    #[clap(long)]
+    nnue: Option<PathBuf>,
 }
 
 pub fn main(args: Args) {
     const TARGET_PATH: &str = "target/release/libchess_bot.so";
-    std::process::Command::new("cargo")
-        .args(["build", "--release", "-p", "chess-bot"])
+    let mut cargo = std::process::Command::new("cargo");
+    cargo.args(["build", "--release", "-p", "chess-bot"]);
+
+    if args.no_default_features {
+        cargo.arg("--no-default-features");
+    }
+
+    if !args.features.is_empty() {
+        cargo.args(["--features", &args.features.join(",")]);
+    }
+
+    // always set, so building without params doesn't keep the ones from the last build
+    let params = args.params.map_or_else(EvalParams::default, |path| {
+        EvalParams::load(&path)
+            .unwrap_or_else(|err| panic!("failed to load {}: {err}", path.display()))
+    });
+    cargo.env("CHESS_BOT_PARAMS", params.to_toml());
+
+    let nnue = args.nnue.map_or_else(String::new, |path| {
+        chess_engine::Network::load(&path)
+            .unwrap_or_else(|err| panic!("failed to load {}: {err}", path.display()));
+        let path = std::fs::canonicalize(&path).unwrap();
+        path.to_str()
+            .expect("the path must be valid UTF-8")
+            .to_owned()
+    });
+    cargo.env("CHESS_BOT_NNUE", nnue);
+
+    cargo
         .stdout(std::process::Stdio::inherit())
         .stderr(std::process::Stdio::inherit())
         .spawn()
@@ -0,0 +1,61 @@
+use std::time::Instant;
+
+use chess_movegen::{Board, PerftTable};
+
+/// Count the leaf nodes of the legal move tree, to find bugs in the move generator
+#[derive(Clone, clap::Parser)]
+pub struct Args {
+    #[clap(value_parser = clap::value_parser!(u16).range(1..))]
+    depth: u16,
+    /// The position to start from, instead of the standard starting position
+    #[clap(long)]
+    fen: Option<Board>,
+    /// Print the count after each legal move of the starting position
+    #[clap(long)]
+    divide: bool,
+    /// Count captures, en passants, castles, promotions, checks and checkmates at the last ply.
+    /// This runs on a single thread
+    #[clap(long, conflicts_with_all = ["divide", "hash"])]
+    stats: bool,
+    /// Store the counts of transpositions in a table of this size, in megabytes. This runs on a
+    /// single thread
+    #[clap(long, conflicts_with = "divide")]
+    hash: Option<usize>,
+    #[clap(long, env = "RAYON_NUM_THREADS", default_value_t = 4)]
+    thread_count: usize,
+}
+
+pub fn main(args: Args) {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.thread_count)
+        .build_global()
+        .unwrap();
+
+    let board = args.fen.unwrap_or_else(Board::standard);
+    let depth = args.depth as usize;
+    let start = Instant::now();
+
+    let nodes = if args.stats {
+        let stats = board.perft_stats(depth);
+        println!("{stats}");
+        stats.nodes
+    } else if let Some(size_mb) = args.hash {
+        board.perft_hashed(depth, &mut PerftTable::new(size_mb))
+    } else if args.divide {
+        let divide = board.perft_divide_parallel(depth);
+        for (mv, nodes) in &divide {
+            println!("{}: {nodes}", mv.uci());
+        }
+        println!();
+        divide.iter().map(|&(_, nodes)| nodes).sum()
+    } else {
+        board.perft_parallel(depth)
+    };
+
+    let elapsed = start.elapsed();
+    println!("Nodes searched: {nodes}");
+    eprintln!(
+        "time {elapsed:?} nps {:.0}",
+        nodes as f64 / elapsed.as_secs_f64()
+    );
+}
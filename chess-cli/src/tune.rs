@@ -0,0 +1,262 @@
+use std::path::{Path, PathBuf};
+
+use chess_engine::EvalParams;
+use chess_movegen::Board;
+use rayon::prelude::*;
+
+/// Tune the evaluation weights on quiet positions from finished games (Texel's tuning method).
+///
+/// The evaluation is mapped to an expected result with a sigmoid, and every weight is nudged
+/// up or down as long as that lowers the mean squared error against the real results
+#[derive(Clone, clap::Parser)]
+pub struct Args {
+    /// One position per line, a fen followed by the result of its game: `1-0`, `0-1` or
+    /// `1/2-1/2`, which may be quoted or in brackets like `[0.5]`
+    dataset: PathBuf,
+    /// Where to write the tuned weights, a `.toml` or `.json` file. It is updated after every
+    /// iteration, so tuning can be stopped at any time
+    #[clap(long, short)]
+    output: PathBuf,
+    /// Start from these weights instead of the defaults
+    #[clap(long)]
+    params: Option<PathBuf>,
+    /// Stop after this many passes over all weights, tuning also stops once no weight changes
+    #[clap(long, default_value_t = 100)]
+    iterations: u32,
+    #[clap(long, env = "RAYON_NUM_THREADS", default_value_t = 4)]
+    thread_count: usize,
+}
+
+struct Position {
+    board: Board,
+    /// From white's point of view, 1 is a win and 0 a loss
+    result: f64,
+}
+
+pub fn main(args: Args) {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.thread_count)
+        .build_global()
+        .unwrap();
+
+    let positions = load_dataset(&args.dataset);
+    if positions.is_empty() {
+        eprintln!("No positions in {}", args.dataset.display());
+        std::process::exit(1);
+    }
+
+    let mut params = match &args.params {
+        Some(path) => EvalParams::load(path).unwrap_or_else(|err| {
+            eprintln!("Could not load {}: {err}", path.display());
+            std::process::exit(1);
+        }),
+        None => EvalParams::default(),
+    };
+
+    let scale = fit_scale(&positions, &params);
+    let mut best = error(&positions, &params, scale);
+    eprintln!(
+        "Loaded {} positions, scale = {scale:.2}, error = {best:.6}",
+        positions.len()
+    );
+
+    for iteration in 1..=args.iterations {
+        let changed = tune_weights(&positions, &mut params, scale, &mut best);
+
+        eprintln!("Iteration {iteration}: error = {best:.6}, changed {changed} weights");
+
+        if let Err(err) = params.save(&args.output) {
+            eprintln!("Could not save {}: {err}", args.output.display());
+            std::process::exit(1);
+        }
+
+        if changed == 0 {
+            break;
+        }
+    }
+
+    eprintln!("Saved the tuned weights to {}", args.output.display());
+}
+
+/// Nudge every weight by one in whichever direction lowers `best`, the current error.
+/// Returns the number of weights which changed
+fn tune_weights(
+    positions: &[Position],
+    params: &mut EvalParams,
+    scale: f64,
+    best: &mut f64,
+) -> usize {
+    let mut changed = 0;
+
+    for i in 0..params.weights_mut().len() {
+        for step in [1, -1] {
+            *params.weights_mut()[i] += step;
+
+            let error = error(positions, params, scale);
+            if error < *best {
+                *best = error;
+                changed += 1;
+                break;
+            }
+
+            *params.weights_mut()[i] -= step;
+        }
+    }
+
+    changed
+}
+
+fn load_dataset(path: &Path) -> Vec<Position> {
+    let dataset = std::fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Could not read {}: {err}", path.display());
+        std::process::exit(1);
+    });
+
+    dataset
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse_position(line).unwrap_or_else(|| {
+                eprintln!("Invalid position on line {}: {line}", i + 1);
+                std::process::exit(1);
+            })
+        })
+        .collect()
+}
+
+fn parse_position(line: &str) -> Option<Position> {
+    let (fen, result) = line.trim().rsplit_once(char::is_whitespace)?;
+
+    let result = match result.trim_matches(|c| matches!(c, '"' | '[' | ']' | ';')) {
+        "1-0" | "1" | "1.0" => 1.0,
+        "0-1" | "0" | "0.0" => 0.0,
+        "1/2-1/2" | "0.5" => 0.5,
+        _ => return None,
+    };
+
+    // epd files store the result in a `c9` opcode, and leave out the move clocks
+    let fen = fen.trim_end().trim_end_matches("c9").trim_end();
+    let board = if fen.split_whitespace().count() == 4 {
+        format!("{fen} 0 1").parse()
+    } else {
+        fen.parse()
+    };
+
+    Some(Position {
+        board: board.ok()?,
+        result,
+    })
+}
+
+/// The expected result for white of a position with `score`
+fn sigmoid(score: i32, scale: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-scale * f64::from(score) / 400.0))
+}
+
+/// The mean squared error between the expected and the real results
+fn error(positions: &[Position], params: &EvalParams, scale: f64) -> f64 {
+    let total: f64 = positions
+        .par_iter()
+        .map(|position| {
+            let score = chess_engine::evaluate(&position.board, params);
+            (position.result - sigmoid(score, scale)).powi(2)
+        })
+        .sum();
+
+    total / positions.len() as f64
+}
+
+/// Find the scale of the sigmoid which fits the untuned weights best, so tuning
+/// doesn't just scale all weights up or down
+fn fit_scale(positions: &[Position], params: &EvalParams) -> f64 {
+    let mut best = (1.0, error(positions, params, 1.0));
+
+    for step in [0.1, 0.01] {
+        let center = best.0;
+
+        for i in -10..=10 {
+            let scale = center + f64::from(i) * step;
+            if scale <= 0.0 {
+                continue;
+            }
+
+            let error = error(positions, params, scale);
+            if error < best.1 {
+                best = (scale, error);
+            }
+        }
+    }
+
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATASET: &str = "\
+rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1 \"1/2-1/2\"
+r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4 [1.0]
+4k3/8/8/8/8/8/3QK3/8 w - - 0 1 1-0
+4k3/3q4/8/8/8/8/4K3/8 b - - 0 1 0-1
+4k3/8/8/3p4/8/8/3PK3/8 w - - c9 \"1/2-1/2\";
+r3k3/8/8/8/8/8/8/4K2R w - - 0 1 0.5
+";
+
+    fn dataset() -> Vec<Position> {
+        DATASET
+            .lines()
+            .map(|line| parse_position(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let positions = dataset();
+        let results: Vec<_> = positions.iter().map(|position| position.result).collect();
+        assert_eq!(results, [0.5, 1.0, 1.0, 0.0, 0.5, 0.5]);
+
+        // the move clocks left out of epd lines default to the start of the game
+        assert_eq!(positions[4].board.full_move_clock(), 1);
+
+        let invalid = [
+            "",
+            "4k3/8/8/8/8/8/3QK3/8 w - - 0 1",
+            "4k3/8/8/8/8/8/3QK3/8 w - - 0 1 2-0",
+            "4k3/8/8/8/8/8/3QK3 w - - 0 1 1-0",
+            "8/8/8/8/8/8/8/8 w - - 0 1 1-0",
+        ];
+        for line in invalid {
+            assert!(parse_position(line).is_none(), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        assert_eq!(sigmoid(0, 1.0), 0.5);
+        assert!(sigmoid(100, 1.0) > 0.5);
+        assert!((sigmoid(100, 1.0) + sigmoid(-100, 1.0) - 1.0).abs() < 1e-12);
+
+        // a larger scale makes the same score more decisive
+        assert!(sigmoid(100, 2.0) > sigmoid(100, 1.0));
+        assert!((sigmoid(400, 1.0) - 10.0 / 11.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_tuning_lowers_the_error() {
+        let positions = dataset();
+        let mut params = EvalParams::default();
+
+        let scale = fit_scale(&positions, &params);
+        assert!(scale > 0.0);
+
+        let start = error(&positions, &params, scale);
+        let mut best = start;
+        let changed = tune_weights(&positions, &mut params, scale, &mut best);
+
+        assert!(changed > 0);
+        assert!(best < start);
+        assert_eq!(best, error(&positions, &params, scale));
+    }
+}
@@ -0,0 +1,199 @@
+use chess_bitboard::{Color, File, Piece, Pos, Rank};
+use chess_movegen::Board;
+
+use crate::{pawns::PawnTable, pieces, EvalParams};
+
+/// The game phase with all pieces on the board, it drops to 0 as pieces are traded
+pub(crate) const MAX_PHASE: i32 = 24;
+
+/// How much each piece contributes to the game phase, pawns and kings don't count
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+
+/// The static evaluation of `board` in centipawns from white's point of view.
+///
+/// Every piece has a separate middlegame and endgame value, which are interpolated by
+/// the game phase, so trading pieces changes the evaluation smoothly. The pawn structure,
+/// mobility and king safety are only evaluated when `positional` is set, and the pawn
+/// structure is cached in `pawns` if there is a table
+pub(crate) fn evaluate(
+    board: &Board,
+    params: &EvalParams,
+    positional: bool,
+    pawns: Option<&mut PawnTable>,
+) -> i32 {
+    let mut middlegame = 0;
+    let mut endgame = 0;
+
+    for color in Color::all() {
+        let sign = match color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+
+        for piece in Piece::all() {
+            let pieces = board[color] & board[piece];
+            let count = i32::from(pieces.count());
+
+            middlegame += sign * count * params.piece_values[piece].middlegame;
+            endgame += sign * count * params.piece_values[piece].endgame;
+
+            if positional {
+                for pos in pieces {
+                    // the tables are from white's point of view
+                    let pos = match color {
+                        Color::White => pos,
+                        Color::Black => pos.flip_rank(),
+                    };
+
+                    middlegame += sign * params.middlegame_tables[piece][pos];
+                    endgame += sign * params.endgame_tables[piece][pos];
+                }
+            }
+        }
+    }
+
+    if positional {
+        let pawns = match pawns {
+            Some(pawns) => pawns.probe(board, params),
+            None => crate::pawns::evaluate(board, params),
+        };
+
+        let terms = pawns + pieces::evaluate(board, params);
+        middlegame += terms.middlegame;
+        endgame += terms.endgame;
+    }
+
+    let phase = phase(board);
+    taper(middlegame, endgame, phase) + taper(0, mop_up(board, params), phase)
+}
+
+/// Interpolate between the middlegame and endgame scores, `phase` goes from
+/// [`MAX_PHASE`] in the opening to 0 in the endgame
+#[inline]
+pub(crate) fn taper(middlegame: i32, endgame: i32, phase: i32) -> i32 {
+    (middlegame * phase + endgame * (MAX_PHASE - phase)) / MAX_PHASE
+}
+
+/// Computed from the pieces which are left, promotions can't make it go past [`MAX_PHASE`]
+pub(crate) fn phase(board: &Board) -> i32 {
+    Piece::all()
+        .map(|piece| i32::from(board[piece].count()) * PHASE_WEIGHTS[piece])
+        .sum::<i32>()
+        .min(MAX_PHASE)
+}
+
+/// The middlegame value of `color`'s pieces
+pub(crate) fn material(board: &Board, params: &EvalParams, color: Color) -> i32 {
+    Piece::all()
+        .map(|piece| {
+            i32::from((board[color] & board[piece]).count()) * params.piece_values[piece].middlegame
+        })
+        .sum()
+}
+
+/// Against a lone king the stronger side needs to drive it to the edge and
+/// bring its own king closer to deliver mate, from white's point of view
+fn mop_up(board: &Board, params: &EvalParams) -> i32 {
+    let lone_king = |color: Color| board[color] == board[color] & board[Piece::King];
+
+    let (stronger, sign) = match (lone_king(Color::White), lone_king(Color::Black)) {
+        (false, true) => (Color::White, 1),
+        (true, false) => (Color::Black, -1),
+        _ => return 0,
+    };
+
+    let stronger_king = board.king_sq(stronger);
+    let weaker_king = board.king_sq(!stronger);
+    let distance = i32::from(chess_lookup::distance(stronger_king, weaker_king));
+
+    let penalty = i32::from(DIST_FROM_EDGE[weaker_king]) + distance * params.mop_up_king_distance;
+
+    -sign * penalty
+}
+
+static DIST_FROM_EDGE: [u8; 64] = {
+    let mut scores = [0; 64];
+
+    let mut i = 0;
+
+    while i < scores.len() {
+        let Some(pos) = Pos::from_u8(i as u8) else {
+            unreachable!()
+        };
+
+        let file = pos.file();
+        let rank = pos.rank();
+
+        let to_a = file.dist_to(File::A);
+        let to_h = file.dist_to(File::H);
+
+        let to_1 = rank.dist_to(Rank::_1);
+        let to_8 = rank.dist_to(Rank::_8);
+
+        let to_file_edge = if to_a < to_h { to_a } else { to_h };
+        let to_rank_edge = if to_1 < to_8 { to_1 } else { to_8 };
+
+        scores[i] = to_file_edge * to_rank_edge * 10
+            + to_file_edge * to_file_edge
+            + to_rank_edge * to_rank_edge;
+
+        i += 1;
+    }
+
+    scores
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirrored_positions() {
+        let params = &EvalParams::default();
+        let mut pawns = PawnTable::default();
+        assert_eq!(evaluate(&Board::standard(), params, true, None), 0);
+
+        let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+            .parse()
+            .unwrap();
+        let mirrored: Board =
+            "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+                .parse()
+                .unwrap();
+
+        for positional in [false, true] {
+            assert_eq!(
+                evaluate(&board, params, positional, Some(&mut pawns)),
+                -evaluate(&mirrored, params, positional, None)
+            );
+        }
+    }
+
+    #[test]
+    fn test_phase_interpolates_values() {
+        assert_eq!(phase(&Board::standard()), MAX_PHASE);
+
+        // only a rook left
+        let board: Board = "8/8/8/4k3/8/8/8/4K2R w - - 0 1".parse().unwrap();
+        assert_eq!(phase(&board), 2);
+
+        assert_eq!(taper(100, 200, MAX_PHASE), 100);
+        assert_eq!(taper(100, 200, MAX_PHASE / 2), 150);
+        assert_eq!(taper(100, 200, 0), 200);
+
+        // the tables use the white point of view, with a1 as the first square
+        let params = EvalParams::default();
+        assert_eq!(params.endgame_tables[Piece::Pawn][Pos::E7], 80);
+        assert_eq!(params.middlegame_tables[Piece::King][Pos::G1], 30);
+    }
+
+    #[test]
+    fn test_mop_up_drives_the_king_to_the_edge() {
+        let center: Board = "8/8/8/4k3/8/8/8/4K2R w - - 0 1".parse().unwrap();
+        let edge: Board = "4k3/8/8/8/8/8/8/4K2R w - - 0 1".parse().unwrap();
+
+        let params = &EvalParams::default();
+        assert!(evaluate(&edge, params, false, None) > evaluate(&center, params, false, None));
+        assert!(evaluate(&center, params, false, None) > 0);
+    }
+}
@@ -1,4 +1,14 @@
+mod eval;
+mod limits;
+mod nnue;
+mod ordering;
+mod params;
+mod pawns;
+mod pieces;
+mod pv;
 mod score;
+mod time;
+mod tt;
 
 #[cfg(any(
     all(target_arch = "wasm32", not(target_os = "wasi")),
@@ -18,20 +28,132 @@ use wasm_instant::Instant;
 )))]
 use std::time::Instant;
 
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
-use chess_bitboard::{BitBoard, Color, Piece};
-use chess_movegen::{Board, ChessMove};
+#[cfg(test)]
+use chess_bitboard::Pos;
+use chess_bitboard::{BitBoard, Color, Piece, PromotionPiece, Rank};
+use chess_movegen::{Board, ChessMove, Game, MoveGen};
 use colorz::Colorize as _;
+pub use limits::SearchLimits;
+pub use nnue::{Network, NetworkError};
+use ordering::{is_quiet, Heuristics, MovePicker};
+#[cfg(feature = "serde")]
+pub use params::ParamsError;
+pub use params::{EvalParams, SquareTable, Tapered};
 pub use score::Score;
+pub use time::{Clock, TimeManager};
+pub use tt::{Bound, TranspositionTable, TtEntry};
+
+/// The static evaluation with all positional terms in centipawns from white's point of view,
+/// which [`Engine`] uses when [`Engine::positional`] is set
+pub fn evaluate(board: &Board, params: &EvalParams) -> i32 {
+    eval::evaluate(board, params, true, None)
+}
 
-#[derive(Default)]
 pub struct Engine {
     pub moves_evaluated: u64,
     pub max_depth: u16,
     pub positional: bool,
+    pub config: SearchConfig,
+    /// The number of threads to search with, both 0 and 1 mean a single threaded search.
+    ///
+    /// The extra threads search the same position and only share their results through
+    /// the transposition table (Lazy SMP). Ignored on wasm, which has no threads
+    pub threads: usize,
+    tt: Arc<TranspositionTable>,
+    params: Arc<EvalParams>,
+    /// Replaces the classical evaluation when set
+    network: Option<Arc<Network>>,
+    accumulators: nnue::Accumulators,
+    pv: pv::PvTable,
+    heuristics: Heuristics,
+    /// Each thread caches the pawn structure separately
+    pawns: pawns::PawnTable,
+    best_pv: Vec<ChessMove>,
+    seldepth: u16,
+    /// The limits of the current search
+    limits: SearchLimits,
+    deadline: Option<Instant>,
+    /// State shared by all threads of a multi-threaded search
+    shared: Option<Arc<SharedSearch>>,
+    /// Whether this engine is one of the extra threads of a multi-threaded search
+    helper: bool,
 }
 
+#[derive(Default)]
+struct SharedSearch {
+    /// Tells the helper threads that the main thread has finished
+    stop: AtomicBool,
+    /// Positions evaluated by the helper threads, reported in batches
+    helper_nodes: AtomicU64,
+}
+
+/// Helper threads add their nodes to the shared count this many at a time
+const HELPER_NODE_BATCH: u64 = 1024;
+
+#[derive(Clone, Copy)]
+struct StopFlag<'a>(&'a AtomicBool);
+
+impl Timeout for StopFlag<'_> {
+    #[inline]
+    fn is_complete(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Toggles for the selective parts of the search, which make it a lot faster at the risk of
+/// overlooking some moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// Skip positions which are so good that even passing the turn would cause a cutoff
+    pub null_move_pruning: bool,
+    /// Search quiet moves which are ordered late at a lower depth first
+    pub late_move_reductions: bool,
+    /// Skip positions and quiet moves near the leaves when the static evaluation is far from the bounds
+    pub futility_pruning: bool,
+    /// Search one ply deeper when in check
+    pub check_extensions: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            null_move_pruning: true,
+            late_move_reductions: true,
+            futility_pruning: true,
+            check_extensions: true,
+        }
+    }
+}
+
+/// Iterative deepening stops at this depth, since deeper entries can't be stored in the
+/// transposition table
+const MAX_DEPTH: u16 = u8::MAX as u16;
+
+/// The initial distance from the previous score to the bounds of the aspiration window
+const ASPIRATION_WINDOW: i32 = 50;
+/// Once the aspiration window would be wider than this, just search the full window
+const MAX_ASPIRATION_WINDOW: i32 = 1000;
+
+const NULL_MOVE_MIN_DEPTH: u16 = 3;
+const NULL_MOVE_REDUCTION: u16 = 2;
+
+/// Futility pruning only applies this many plies from the leaves
+const FUTILITY_DEPTH: u16 = 2;
+const FUTILITY_MARGIN: i32 = 150;
+
+/// Late move reductions only apply after this many moves have been searched
+const LMR_MIN_MOVES: u32 = 3;
+const LMR_MIN_DEPTH: u16 = 3;
+
 #[derive(Default)]
 pub struct ThreeFold {
     boards: HashMap<Board, u8, IntHashBuilder>,
@@ -100,6 +222,65 @@ impl Timeout for DurationTimeout {
 pub trait Timeout {
     #[must_use]
     fn is_complete(&self) -> bool;
+
+    /// Called after every completed iteration with its result, returns whether the search
+    /// should stop instead of starting the next iteration
+    #[must_use]
+    #[inline]
+    fn stop_after_iteration(&self, depth: u16, best_move: Option<ChessMove>, score: Score) -> bool {
+        let _ = (depth, best_move, score);
+        self.is_complete()
+    }
+}
+
+/// Never completes, for searches which are only bounded by [`SearchLimits`]
+impl Timeout for () {
+    #[inline]
+    fn is_complete(&self) -> bool {
+        false
+    }
+}
+
+/// A summary of the search after completing an iteration
+#[derive(Debug, Clone, Copy)]
+pub struct SearchInfo<'a> {
+    /// The number of plies searched at the root
+    pub depth: u16,
+    /// The deepest ply reached, including extensions
+    pub seldepth: u16,
+    pub score: Score,
+    /// The number of positions evaluated so far
+    pub nodes: u64,
+    pub elapsed: Duration,
+    /// Permille of the transposition table in use, see [`TranspositionTable::hashfull`]
+    pub hashfull: u16,
+    /// The expected line of play, starting with the best move
+    pub pv: &'a [ChessMove],
+}
+
+impl SearchInfo<'_> {
+    /// Nodes per second
+    pub fn nps(&self) -> u64 {
+        let micros = self.elapsed.as_micros().max(1);
+        (u128::from(self.nodes) * 1_000_000 / micros) as u64
+    }
+}
+
+/// Gets notified about the progress of a search
+pub trait SearchObserver {
+    fn on_iteration(&mut self, info: &SearchInfo<'_>);
+}
+
+impl SearchObserver for () {
+    #[inline]
+    fn on_iteration(&mut self, _info: &SearchInfo<'_>) {}
+}
+
+impl<F: FnMut(&SearchInfo<'_>)> SearchObserver for F {
+    #[inline]
+    fn on_iteration(&mut self, info: &SearchInfo<'_>) {
+        self(info)
+    }
 }
 
 impl<T: Timeout + Copy> TimeoutRef for T {}
@@ -110,18 +291,39 @@ impl<T: ?Sized + Timeout> Timeout for &T {
     fn is_complete(&self) -> bool {
         T::is_complete(self)
     }
+
+    #[inline]
+    fn stop_after_iteration(&self, depth: u16, best_move: Option<ChessMove>, score: Score) -> bool {
+        T::stop_after_iteration(self, depth, best_move, score)
+    }
 }
 
 trait Policy {
     type Flip: Policy<Flip = Self>;
     const COLOR: Color;
     const WORST_SCORE: Score;
-    const BEST_SCORE: Score;
 
     const IS_BETA_CUTOFF: bool = false;
 
+    const PROMOTION_RANK: Rank;
+
     fn is_better(score: Score, new: Score) -> bool;
 
+    /// The bound which this side is trying to improve
+    fn own_bound(alpha: Score, beta: Score) -> Score;
+
+    /// Move `score` by `centipawns` in favour of this side
+    fn gain(score: Score, centipawns: i32) -> Score;
+
+    /// Whether `score` is so good for this side that the other side will avoid this position
+    fn fails_high(score: Score, alpha: Score, beta: Score) -> bool;
+
+    /// The narrowest window which can tell if a score fails high
+    fn null_window(alpha: Score, beta: Score) -> (Score, Score);
+
+    /// The narrowest window which can tell if a score improves on this side's bound
+    fn pvs_window(alpha: Score, beta: Score) -> (Score, Score);
+
     fn update_cutoff(alpha: &mut Score, beta: &mut Score, score: Score);
 }
 
@@ -133,14 +335,35 @@ impl Policy for White {
     const COLOR: Color = Color::White;
 
     const WORST_SCORE: Score = Score::Min;
-    const BEST_SCORE: Score = Score::Max;
 
     const IS_BETA_CUTOFF: bool = true;
 
+    const PROMOTION_RANK: Rank = Rank::_8;
+
     fn is_better(score: Score, new: Score) -> bool {
         score < new
     }
 
+    fn own_bound(alpha: Score, _beta: Score) -> Score {
+        alpha
+    }
+
+    fn fails_high(score: Score, _alpha: Score, beta: Score) -> bool {
+        score >= beta
+    }
+
+    fn null_window(_alpha: Score, beta: Score) -> (Score, Score) {
+        (beta.next_down(), beta)
+    }
+
+    fn pvs_window(alpha: Score, _beta: Score) -> (Score, Score) {
+        (alpha, alpha.next_up())
+    }
+
+    fn gain(score: Score, centipawns: i32) -> Score {
+        score.offset(centipawns)
+    }
+
     fn update_cutoff(alpha: &mut Score, _beta: &mut Score, score: Score) {
         *alpha = score.max(*alpha)
     }
@@ -151,14 +374,35 @@ impl Policy for Black {
     const COLOR: Color = Color::Black;
 
     const WORST_SCORE: Score = Score::Max;
-    const BEST_SCORE: Score = Score::Min;
 
     const IS_BETA_CUTOFF: bool = false;
 
+    const PROMOTION_RANK: Rank = Rank::_1;
+
     fn is_better(score: Score, new: Score) -> bool {
         score > new
     }
 
+    fn own_bound(_alpha: Score, beta: Score) -> Score {
+        beta
+    }
+
+    fn fails_high(score: Score, alpha: Score, _beta: Score) -> bool {
+        score <= alpha
+    }
+
+    fn null_window(alpha: Score, _beta: Score) -> (Score, Score) {
+        (alpha, alpha.next_up())
+    }
+
+    fn pvs_window(_alpha: Score, beta: Score) -> (Score, Score) {
+        (beta.next_down(), beta)
+    }
+
+    fn gain(score: Score, centipawns: i32) -> Score {
+        score.offset(-centipawns)
+    }
+
     fn update_cutoff(_alpha: &mut Score, beta: &mut Score, score: Score) {
         *beta = score.min(*beta)
     }
@@ -189,6 +433,17 @@ struct BoardList<'a> {
     count: u8,
 }
 
+impl From<&Game> for ThreeFold {
+    /// The positions of `game` so far, which the search must not repeat
+    fn from(game: &Game) -> Self {
+        let mut three_fold = ThreeFold::new();
+        for &board in game.boards() {
+            three_fold.add(board);
+        }
+        three_fold
+    }
+}
+
 impl ThreeFold {
     pub fn new() -> Self {
         ThreeFold {
@@ -240,16 +495,177 @@ impl<'a> BoardList<'a> {
     }
 }
 
+impl Default for Engine {
+    fn default() -> Self {
+        Self::with_table(Arc::default())
+    }
+}
+
 impl Engine {
+    /// Create an engine with a transposition table of roughly `size_mb` megabytes
+    pub fn with_hash_size(size_mb: usize) -> Self {
+        Self::with_table(Arc::new(TranspositionTable::new(size_mb)))
+    }
+
+    /// Create an engine which searches with `tt`, without allocating a table of its own
+    fn with_table(tt: Arc<TranspositionTable>) -> Self {
+        Self {
+            moves_evaluated: 0,
+            max_depth: 0,
+            positional: false,
+            config: SearchConfig::default(),
+            threads: 0,
+            tt,
+            params: Arc::default(),
+            network: None,
+            accumulators: nnue::Accumulators::default(),
+            pv: pv::PvTable::default(),
+            heuristics: Heuristics::default(),
+            pawns: pawns::PawnTable::default(),
+            best_pv: Vec::new(),
+            seldepth: 0,
+            limits: SearchLimits::default(),
+            deadline: None,
+            shared: None,
+            helper: false,
+        }
+    }
+
+    /// Replace the transposition table with an empty one of roughly `size_mb` megabytes
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        self.tt = Arc::new(TranspositionTable::new(size_mb));
+    }
+
+    /// Forget all previously searched positions, e.g. when starting a new game
+    pub fn clear_hash(&mut self) {
+        self.tt.clear();
+    }
+
+    #[inline]
+    pub fn transposition_table(&self) -> &TranspositionTable {
+        &self.tt
+    }
+
+    #[inline]
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// Evaluate positions with a different set of weights, e.g. loaded from a file
+    pub fn set_params(&mut self, params: EvalParams) {
+        self.params = Arc::new(params);
+        self.pawns.clear();
+    }
+
+    #[inline]
+    pub fn network(&self) -> Option<&Network> {
+        self.network.as_deref()
+    }
+
+    /// Evaluate positions with an efficiently updatable neural network instead of
+    /// the classical evaluation, or go back to the classical evaluation with `None`
+    pub fn set_network(&mut self, network: Option<Network>) {
+        self.network = network.map(Arc::new);
+    }
+
+    #[inline]
+    fn is_complete(&self, timeout: impl Timeout) -> bool {
+        timeout.is_complete()
+            || self.limits.nodes.is_some_and(|limit| self.nodes() >= limit)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// The number of positions evaluated in the current search by all threads
+    #[inline]
+    fn nodes(&self) -> u64 {
+        match &self.shared {
+            Some(shared) if !self.helper => {
+                self.moves_evaluated + shared.helper_nodes.load(Ordering::Relaxed)
+            }
+            _ => self.moves_evaluated,
+        }
+    }
+
+    #[cfg(any(
+        all(target_arch = "wasm32", not(target_os = "wasi")),
+        target_arch = "asmjs"
+    ))]
+    fn thread_count(&self) -> usize {
+        1
+    }
+
+    #[cfg(not(any(
+        all(target_arch = "wasm32", not(target_os = "wasi")),
+        target_arch = "asmjs"
+    )))]
+    fn thread_count(&self) -> usize {
+        self.threads.max(1)
+    }
+
+    /// An engine for an extra thread of a multi-threaded search, which shares
+    /// the transposition table with this one
+    fn helper(&self, shared: &Arc<SharedSearch>) -> Self {
+        Self {
+            positional: self.positional,
+            config: self.config,
+            params: self.params.clone(),
+            network: self.network.clone(),
+            shared: Some(shared.clone()),
+            helper: true,
+            ..Self::with_table(self.tt.clone())
+        }
+    }
+
+    /// The principal variation of the last completed iteration
+    #[inline]
+    pub fn principal_variation(&self) -> &[ChessMove] {
+        &self.best_pv
+    }
+
     pub fn search(
         &mut self,
         board: &Board,
         three_fold: &ThreeFold,
         timeout: impl TimeoutRef,
     ) -> (Option<ChessMove>, Score) {
+        self.search_observed(board, three_fold, timeout, ())
+    }
+
+    /// Search like [`Engine::search`], and notify `observer` after every completed iteration
+    pub fn search_observed(
+        &mut self,
+        board: &Board,
+        three_fold: &ThreeFold,
+        timeout: impl TimeoutRef,
+        observer: impl SearchObserver,
+    ) -> (Option<ChessMove>, Score) {
+        self.search_limited(
+            board,
+            three_fold,
+            SearchLimits::default(),
+            timeout,
+            observer,
+        )
+    }
+
+    /// Search like [`Engine::search_observed`], but also stop as soon as any of `limits` is
+    /// reached. Use `()` as the `timeout` to only be bounded by the `limits`
+    pub fn search_limited(
+        &mut self,
+        board: &Board,
+        three_fold: &ThreeFold,
+        limits: SearchLimits,
+        timeout: impl TimeoutRef,
+        observer: impl SearchObserver,
+    ) -> (Option<ChessMove>, Score) {
+        self.limits = limits;
+        self.deadline = limits.move_time.map(|time| Instant::now() + time);
+
         match board.turn() {
-            Color::White => self.search_with::<White>(board, three_fold, timeout),
-            Color::Black => self.search_with::<Black>(board, three_fold, timeout),
+            Color::White => self.search_with::<White>(board, three_fold, timeout, observer),
+            Color::Black => self.search_with::<Black>(board, three_fold, timeout, observer),
         }
     }
 
@@ -258,112 +674,232 @@ impl Engine {
         board: &Board,
         three_fold: &ThreeFold,
         timeout: impl TimeoutRef,
+        observer: impl SearchObserver,
     ) -> (Option<ChessMove>, Score) {
         assert_eq!(P::COLOR, board.turn());
+        self.tt.new_search();
+
+        let threads = self.thread_count();
+        if threads == 1 {
+            return self.search_thread::<P>(board, three_fold, timeout, observer, 0);
+        }
+
+        let shared = Arc::new(SharedSearch::default());
+        self.shared = Some(shared.clone());
+
+        let result = std::thread::scope(|scope| {
+            let helpers = (1..threads)
+                .map(|i| {
+                    let mut helper = self.helper(&shared);
+                    let stop = StopFlag(&shared.stop);
+
+                    scope.spawn(move || {
+                        // start half of the helpers a ply deeper, so the threads spread out over
+                        // different depths instead of all searching the same tree
+                        helper.search_thread::<P>(board, three_fold, stop, (), i as u16 % 2);
+                        helper.moves_evaluated
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let result = self.search_thread::<P>(board, three_fold, timeout, observer, 0);
+
+            shared.stop.store(true, Ordering::Relaxed);
+            for helper in helpers {
+                self.moves_evaluated += helper.join().expect("helper search thread panicked");
+            }
+
+            result
+        });
+
+        self.shared = None;
+        result
+    }
+
+    /// Iterative deepening on the current thread, starting at `depth`
+    fn search_thread<P: Policy>(
+        &mut self,
+        board: &Board,
+        three_fold: &ThreeFold,
+        timeout: impl TimeoutRef,
+        mut observer: impl SearchObserver,
+        mut depth: u16,
+    ) -> (Option<ChessMove>, Score) {
+        let start = Instant::now();
         self.moves_evaluated = 0;
+        self.best_pv.clear();
+        self.heuristics.new_search();
 
-        let mut best_score = P::WORST_SCORE;
-        let mut best_mv = None;
+        if let Some(network) = &self.network {
+            self.accumulators.refresh(network, 0, board);
+        }
 
-        let mut depth = 0;
+        let mut best_score = P::WORST_SCORE;
+        let mut best_mv = self
+            .tt
+            .probe(board.zobrist(), 0)
+            .and_then(|entry| entry.best_move)
+            .filter(|&mv| board.is_legal(mv));
 
         loop {
             tracing::debug!(color = ?P::COLOR, depth, board=%board, "start depth");
-            let mut score = P::WORST_SCORE;
-            let mut best_mv_at = None;
-
-            let mut args = AlphaBetaArgs {
-                old_board: board,
-                timeout,
-                remaining_depth: depth,
-                current_depth: 1,
-                alpha: Score::Min,
-                beta: Score::Max,
-                list: BoardList::new(board, three_fold),
-            };
 
-            let mut moves = board.legals();
-
-            if let Some(mv) = best_mv {
-                tracing::debug!("Consider previous best move");
-                moves.remove_move(mv);
+            // expect the score to stay close to the one from the previous iteration
+            let mut delta = ASPIRATION_WINDOW;
+            let (mut alpha, mut beta) = best_score.window(delta);
+
+            let (score, best_mv_at) = loop {
+                let args = AlphaBetaArgs {
+                    old_board: board,
+                    timeout,
+                    remaining_depth: depth,
+                    current_depth: 1,
+                    alpha,
+                    beta,
+                    list: BoardList::new(board, three_fold),
+                };
 
-                let new = self.alphabeta::<P::Flip>(mv, &args);
+                let (score, best_mv_at) = self.search_root::<P>(args, best_mv);
 
-                if timeout.is_complete() {
-                    break;
-                }
+                let fail_low = score <= alpha && alpha != Score::Min;
+                let fail_high = score >= beta && beta != Score::Max;
 
-                if P::is_better(score, new) {
-                    score = new;
-                    best_mv_at = Some(mv);
-                    tracing::debug!(color = ?P::COLOR, depth, "move"=%mv, board=%board, ?score, "{}", "better".bright_green());
+                if self.is_complete(timeout) || !(fail_low || fail_high) {
+                    break (score, best_mv_at);
                 }
 
-                P::update_cutoff(&mut args.alpha, &mut args.beta, score)
-            }
+                tracing::debug!(color = ?P::COLOR, depth, ?alpha, ?beta, ?score, "{}", "aspiration window failed".red());
 
-            // iterate over captures first
-            moves.set_mask(board[!P::COLOR]);
+                delta = delta.saturating_mul(4);
+                (alpha, beta) = match score {
+                    Score::Raw(_) if delta <= MAX_ASPIRATION_WINDOW => {
+                        if fail_low {
+                            (score.offset(-delta), beta)
+                        } else {
+                            (alpha, score.offset(delta))
+                        }
+                    }
+                    _ => (Score::Min, Score::Max),
+                };
+            };
 
-            if !moves.is_empty() {
-                tracing::debug!("Consider captures");
+            if self.is_complete(timeout) {
+                tracing::info!("Timeout");
+                break;
             }
 
-            for mv in &mut moves {
-                let new = self.alphabeta::<P::Flip>(mv, &args);
+            best_mv = best_mv_at;
+            best_score = score;
+            self.max_depth = depth;
+            self.tt
+                .store(board.zobrist(), 0, depth, Bound::Exact, score, best_mv);
+            depth += 1;
 
-                if timeout.is_complete() {
-                    break;
-                }
+            self.best_pv.clear();
+            self.best_pv.extend_from_slice(self.pv.line(0));
+            self.extend_pv_from_tt(board);
+
+            observer.on_iteration(&SearchInfo {
+                depth,
+                seldepth: self.seldepth.max(depth),
+                score,
+                nodes: self.nodes(),
+                elapsed: start.elapsed(),
+                hashfull: self.tt.hashfull(),
+                pv: &self.best_pv,
+            });
+
+            // deeper searches can't find a shorter mate
+            match score {
+                Score::BlackMateIn(_) | Score::WhiteMateIn(_) if !self.limits.infinite => break,
+                _ => (),
+            }
 
-                if P::is_better(score, new) {
-                    score = new;
-                    best_mv_at = Some(mv);
-                    tracing::debug!(color = ?P::COLOR, depth, "move"=%mv, board=%board, ?score, "{}", "better".bright_green());
-                }
+            if depth >= MAX_DEPTH || self.limits.max_depth().is_some_and(|limit| depth >= limit) {
+                break;
+            }
 
-                P::update_cutoff(&mut args.alpha, &mut args.beta, score)
+            if timeout.stop_after_iteration(depth, best_mv, score) {
+                break;
             }
+        }
 
-            tracing::debug!("Consider normal moves");
+        (best_mv, best_score)
+    }
 
-            // clear mask
-            moves.set_mask(!BitBoard::empty());
+    fn search_root<P: Policy>(
+        &mut self,
+        mut args: AlphaBetaArgs<'_, impl TimeoutRef>,
+        best_mv: Option<ChessMove>,
+    ) -> (Score, Option<ChessMove>) {
+        let board = args.old_board;
+        let mut score = P::WORST_SCORE;
+        let mut best_mv_at = None;
+        self.seldepth = 0;
+        self.pv.clear(0);
 
-            for mv in moves {
-                let new = self.alphabeta::<P::Flip>(mv, &args);
+        let moves = MovePicker::new(board, board.legals(), best_mv, None, 0, &self.heuristics);
 
-                if timeout.is_complete() {
-                    break;
-                }
+        for (searched, mv) in (0..).zip(moves) {
+            // moves at the root are never reduced
+            let new = if searched == 0 {
+                self.alphabeta::<P::Flip>(mv, &args)
+            } else {
+                self.search_later_move::<P>(mv, &args, false, searched)
+            };
 
-                if P::is_better(score, new) {
-                    score = new;
-                    best_mv_at = Some(mv);
-                    tracing::debug!(color = ?P::COLOR, depth, "move"=%mv, board=%board, ?score, "{}", "better".bright_green());
-                }
+            if self.is_complete(args.timeout) {
+                break;
+            }
 
-                P::update_cutoff(&mut args.alpha, &mut args.beta, score)
+            if P::is_better(score, new) {
+                score = new;
+                best_mv_at = Some(mv);
+                self.pv.update(0, mv);
+                tracing::debug!(color = ?P::COLOR, depth=args.remaining_depth, "move"=%mv, board=%board, ?score, "{}", "better".bright_green());
             }
 
-            if timeout.is_complete() {
-                tracing::info!("Timeout");
+            P::update_cutoff(&mut args.alpha, &mut args.beta, score);
+
+            // the score is outside of the aspiration window
+            if args.beta <= args.alpha {
                 break;
             }
+        }
 
-            best_mv = best_mv_at;
-            best_score = score;
-            self.max_depth = depth;
-            depth += 1;
+        (score, best_mv_at)
+    }
 
-            match score {
-                Score::BlackMateIn(_) | Score::WhiteMateIn(_) => break,
-                _ => (),
-            }
+    /// Transposition table hits cut the principal variation short, so
+    /// follow the best moves stored in the table to recover the rest of it
+    fn extend_pv_from_tt(&mut self, board: &Board) {
+        let mut board = *board;
+        let mut seen = ThreeFold::new();
+
+        for &mv in &self.best_pv {
+            seen.add(board);
+            unsafe { board.move_unchecked_mut(mv) };
         }
 
-        (best_mv, best_score)
+        while self.best_pv.len() < usize::from(self.seldepth.max(1)) {
+            // stop at repetitions, otherwise the line could go on forever
+            seen.add(board);
+            if seen.get(&board) > 1 {
+                break;
+            }
+
+            let Some(mv) = self
+                .tt
+                .probe(board.zobrist(), 0)
+                .and_then(|entry| entry.best_move)
+                .filter(|&mv| board.is_legal(mv))
+            else {
+                break;
+            };
+
+            self.best_pv.push(mv);
+            unsafe { board.move_unchecked_mut(mv) };
+        }
     }
 
     fn alphabeta<P: Policy>(
@@ -371,8 +907,20 @@ impl Engine {
         mv: ChessMove,
         args: &AlphaBetaArgs<'_, impl TimeoutRef>,
     ) -> Score {
-        let board = unsafe { args.old_board.move_unchecked(mv) };
-        let was_capture = args.old_board.raw().get(mv.dest).is_some();
+        let board = if mv == ChessMove::NULL {
+            if let Some(network) = &self.network {
+                self.accumulators.copy(network, args.current_depth);
+            }
+
+            args.old_board
+                .null_move()
+                .expect("null moves are only made when not in check")
+        } else {
+            unsafe { self.make_move(args.old_board, mv, args.current_depth) }
+        };
+        self.pv.clear(args.current_depth);
+        self.seldepth = self.seldepth.max(args.current_depth);
+        let was_capture = mv != ChessMove::NULL && args.old_board.is_capture(mv);
         let list = if was_capture {
             BoardList::new(&board, args.list.three_fold)
         } else {
@@ -405,7 +953,7 @@ impl Engine {
             );
         }
 
-        if was_capture && self.insuffient_material(&board) {
+        if was_capture && board.has_insufficient_material() {
             tracing::trace!(
                 current_depth=args.current_depth,
                 depth=args.remaining_depth,
@@ -420,7 +968,7 @@ impl Engine {
             return Score::Raw(0);
         }
 
-        let mut moves = board.legals();
+        let moves = board.legals();
 
         if moves.is_empty() {
             if board.in_check() {
@@ -484,15 +1032,24 @@ impl Engine {
             return Score::Raw(0);
         }
 
-        let mut is_complete = args.remaining_depth == 0;
-        if is_complete && was_capture {
-            moves.set_mask(board[!P::COLOR]);
+        let in_check = board.in_check();
 
-            is_complete = moves.is_empty()
-        }
+        // don't stop searching in the middle of a forcing sequence
+        let remaining_depth = if in_check && self.config.check_extensions {
+            args.remaining_depth + 1
+        } else {
+            args.remaining_depth
+        };
 
-        if is_complete {
-            let score = self.eval(&board, args.current_depth);
+        if remaining_depth == 0 {
+            let score = self.quiescence::<P>(
+                &board,
+                moves,
+                args.current_depth,
+                args.alpha,
+                args.beta,
+                args.timeout,
+            );
 
             tracing::trace!(
                 current_depth=args.current_depth,
@@ -504,30 +1061,159 @@ impl Engine {
                 was_capture,
                 board=%args.old_board,
                 ?score,
-                "{}", "eval".bright_blue()
+                "{}", "quiescence".bright_blue()
             );
 
             return score;
         }
 
+        let key = board.zobrist();
+        let mut tt_move = None;
+
+        if let Some(entry) = self.tt.probe(key, args.current_depth) {
+            if entry.depth >= remaining_depth {
+                let is_cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= args.beta,
+                    Bound::Upper => entry.score <= args.alpha,
+                };
+
+                if is_cutoff {
+                    tracing::trace!(
+                        current_depth=args.current_depth,
+                        depth=args.remaining_depth,
+                        color=?P::COLOR,
+                        alpha=?args.alpha,
+                        beta=?args.beta,
+                        score=?entry.score,
+                        bound=?entry.bound,
+                        "move"=%mv,
+                        board=%args.old_board,
+                        "{}", "tt hit".bright_blue()
+                    );
+                    return entry.score;
+                }
+            }
+
+            tt_move = entry.best_move;
+        }
+
+        let (alpha, beta) = (args.alpha, args.beta);
+        let (ply, depth) = (args.current_depth, remaining_depth);
+        let prev = (mv != ChessMove::NULL).then_some(mv);
+
+        // the static evaluation is unreliable in check and in the endgame
+        let static_eval = (!in_check && self.is_middlegame(&board)).then(|| self.eval(&board, ply));
+
+        if let Some(eval) = static_eval {
+            let margin = FUTILITY_MARGIN * i32::from(depth);
+
+            if self.config.futility_pruning
+                && depth <= FUTILITY_DEPTH
+                && P::fails_high(P::gain(eval, -margin), alpha, beta)
+            {
+                tracing::trace!(
+                    current_depth=ply,
+                    depth,
+                    color=?P::COLOR,
+                    ?alpha,
+                    ?beta,
+                    ?eval,
+                    "move"=%mv,
+                    board=%args.old_board,
+                    "{}", "reverse futility".bright_blue()
+                );
+                return eval;
+            }
+
+            // passing the turn is illegal, so this must not happen twice in a row. And in
+            // pawn endgames passing may actually be the best move, so it can't be used there
+            if self.config.null_move_pruning
+                && prev.is_some()
+                && depth >= NULL_MOVE_MIN_DEPTH
+                && P::fails_high(eval, alpha, beta)
+                && (board[P::COLOR] - board[Piece::Pawn] - board[Piece::King]).any()
+            {
+                let (null_alpha, null_beta) = P::null_window(alpha, beta);
+                let null_args = AlphaBetaArgs {
+                    old_board: &board,
+                    timeout: args.timeout,
+                    remaining_depth: depth.saturating_sub(1 + NULL_MOVE_REDUCTION),
+                    current_depth: ply + 1,
+                    alpha: null_alpha,
+                    beta: null_beta,
+                    list,
+                };
+
+                let new = self.alphabeta::<P::Flip>(ChessMove::NULL, &null_args);
+
+                if !self.is_complete(args.timeout) && P::fails_high(new, alpha, beta) {
+                    tracing::trace!(
+                        current_depth=ply,
+                        depth,
+                        color=?P::COLOR,
+                        ?alpha,
+                        ?beta,
+                        score=?new,
+                        "move"=%mv,
+                        board=%args.old_board,
+                        "{}", "null move cutoff".bright_blue()
+                    );
+
+                    // mates found after passing the turn aren't real
+                    return match new {
+                        Score::Raw(_) => new,
+                        _ => eval,
+                    };
+                }
+            }
+        }
+
         let mut score = P::WORST_SCORE;
+        let mut best_mv = None;
 
         let mut args = AlphaBetaArgs {
             old_board: &board,
             timeout: args.timeout,
-            remaining_depth: args.remaining_depth.saturating_sub(1),
-            current_depth: args.current_depth + 1,
-            alpha: args.alpha,
-            beta: args.beta,
+            remaining_depth: depth - 1,
+            current_depth: ply + 1,
+            alpha,
+            beta,
             list,
         };
 
+        // the table may contain an unrelated position with the same key, but
+        // the tt move is only used to order the legal moves so that is fine
+        let moves = MovePicker::new(&board, moves, tt_move, prev, ply, &self.heuristics);
+        let mut searched = 0;
+
         for mv in moves {
-            if args.timeout.is_complete() {
+            if self.is_complete(args.timeout) {
                 break;
             }
 
-            let new = self.alphabeta::<P::Flip>(mv, &args);
+            let quiet = is_quiet(&board, mv);
+
+            if let Some(eval) = static_eval {
+                let margin = FUTILITY_MARGIN * i32::from(depth);
+
+                // this quiet move is unlikely to make up for how bad the position is
+                if self.config.futility_pruning
+                    && quiet
+                    && searched > 0
+                    && depth <= FUTILITY_DEPTH
+                    && !P::is_better(P::own_bound(args.alpha, args.beta), P::gain(eval, margin))
+                {
+                    continue;
+                }
+            }
+
+            let new = if searched == 0 {
+                self.alphabeta::<P::Flip>(mv, &args)
+            } else {
+                self.search_later_move::<P>(mv, &args, quiet && !in_check, searched)
+            };
+            searched += 1;
 
             let old_score = score;
             if P::is_better(score, new) {
@@ -548,6 +1234,8 @@ impl Engine {
                     "better".bright_green()
                 );
                 score = new;
+                best_mv = Some(mv);
+                self.pv.update(ply, mv);
             } else {
                 tracing::trace!(
                     current_depth=args.current_depth,
@@ -588,6 +1276,7 @@ impl Engine {
                         "alpha cutoff"
                     }.bright_green()
                 );
+                self.heuristics.record_cutoff(&board, prev, mv, ply, depth);
                 break;
             }
         }
@@ -607,306 +1296,267 @@ impl Engine {
         if tracing::enabled!(tracing::Level::TRACE) {
             eprintln!();
         }
+
+        if !self.is_complete(args.timeout) {
+            let bound = if score <= alpha {
+                Bound::Upper
+            } else if score >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+
+            self.tt.store(key, ply, depth, bound, score, best_mv);
+        }
+
         score
     }
 
-    fn eval(&mut self, board: &Board, current_depth: u16) -> Score {
-        self.moves_evaluated += 1;
+    /// Only search captures and promotions until the position is quiet, so that
+    /// positions are never evaluated in the middle of an exchange.
+    ///
+    /// `moves` are the legal moves in `board`, which must not be empty
+    fn quiescence<P: Policy>(
+        &mut self,
+        board: &Board,
+        mut moves: MoveGen,
+        ply: u16,
+        mut alpha: Score,
+        mut beta: Score,
+        timeout: impl TimeoutRef,
+    ) -> Score {
+        self.seldepth = self.seldepth.max(ply);
 
-        if board.half_move_clock() >= 100 {
-            return Score::Raw(0);
-        }
+        let in_check = board.in_check();
+        let mut score = P::WORST_SCORE;
+        let mut stand_pat = None;
 
-        let white_piece_score = self.score_pieces(board, Color::White);
-        let black_piece_score = self.score_pieces(board, Color::Black);
+        // the side to move may stand pat instead of capturing anything,
+        // unless it is in check and every evasion needs to be searched
+        if !in_check {
+            let eval = self.eval(board, ply);
+            score = eval;
 
-        let piece_score = white_piece_score - black_piece_score;
+            P::update_cutoff(&mut alpha, &mut beta, score);
+            if beta <= alpha {
+                return score;
+            }
 
-        let mut white_endgame_score = 0;
-        let mut black_endgame_score = 0;
+            let mut mask = board[!P::COLOR] | BitBoard::from_rank(P::PROMOTION_RANK);
+            if let Some(ep) = board.enpassant_pos() {
+                mask |= BitBoard::from(ep);
+            }
+            moves.set_mask(mask);
 
-        let is_endgame = match piece_score.cmp(&0) {
-            std::cmp::Ordering::Less => {
-                if black_piece_score < 800 + 500 * 2 {
-                    white_endgame_score += self.eval_endgame(board, Color::Black);
-                    true
-                } else {
-                    false
-                }
+            // the endgame evaluation swings too much for delta pruning to be reliable
+            if self.is_middlegame(board) {
+                stand_pat = Some(eval);
             }
-            std::cmp::Ordering::Equal => {
-                black_piece_score < 800 + 500 * 2 && white_piece_score < 800 + 500 * 2
+        }
+
+        let moves = MovePicker::captures(
+            board,
+            moves.filter(|mv| {
+                in_check
+                    || match mv.piece {
+                        // under promotions are almost never better than promoting to a queen
+                        Some(piece) => piece == PromotionPiece::Queen,
+                        // skip quiet moves onto the promotion rank
+                        None => board.is_capture(*mv),
+                    }
+            }),
+        );
+
+        for mv in moves {
+            if self.is_complete(timeout) {
+                break;
             }
-            std::cmp::Ordering::Greater => {
-                if white_piece_score < 800 + 500 * 2 {
-                    black_endgame_score += self.eval_endgame(board, Color::White);
-                    true
-                } else {
-                    false
+
+            let captured = board.captured_piece(mv);
+
+            if let Some(stand_pat) = stand_pat {
+                let mut gain = captured.map_or(0, |piece| self.piece_value(piece)) + DELTA_MARGIN;
+                if mv.piece.is_some() {
+                    gain += self.piece_value(Piece::Queen) - self.piece_value(Piece::Pawn);
                 }
-            }
-        };
 
-        let king_pos_score = if self.positional {
-            if is_endgame {
-                i32::from(WHITE_KING_END_GAME_MAP[board.king_sq(Color::White)])
-                    - i32::from(WHITE_KING_END_GAME_MAP[board.king_sq(Color::Black).flip_rank()])
-            } else {
-                i32::from(WHITE_KING_EARLY_GAME_MAP[board.king_sq(Color::White)])
-                    - i32::from(WHITE_KING_EARLY_GAME_MAP[board.king_sq(Color::Black).flip_rank()])
+                if !P::is_better(P::own_bound(alpha, beta), P::gain(stand_pat, gain)) {
+                    continue;
+                }
             }
-        } else {
-            0
-        };
 
-        let white_score = white_piece_score + white_endgame_score;
-        let black_score = black_piece_score + black_endgame_score;
+            // captures which lose material are very unlikely to raise the score
+            if !in_check && !board.see_ge(mv, 0) {
+                continue;
+            }
 
-        tracing::trace!(current_depth, ?white_score, ?black_score);
+            let new_board = unsafe { self.make_move(board, mv, ply + 1) };
+            let new = self.quiescence_move::<P::Flip>(&new_board, ply + 1, alpha, beta, timeout);
 
-        // assert!(white_score > black_score);
+            if P::is_better(score, new) {
+                score = new;
+            }
 
-        let piece_score = white_score - black_score + king_pos_score;
+            P::update_cutoff(&mut alpha, &mut beta, score);
+            if beta <= alpha {
+                break;
+            }
+        }
 
-        Score::Raw(piece_score)
+        score
     }
 
-    fn eval_endgame(&mut self, board: &Board, better: Color) -> i32 {
-        // if we are in the endgame
-        let better_king = board.king_sq(better);
-        let worse_king = board.king_sq(!better);
+    /// Moves after the first one are expected to be worse, so first prove that with a
+    /// null window search, which may also be reduced for `quiet` moves, and only
+    /// search them with the full window if that fails
+    fn search_later_move<P: Policy>(
+        &mut self,
+        mv: ChessMove,
+        args: &AlphaBetaArgs<'_, impl TimeoutRef>,
+        quiet: bool,
+        searched: u32,
+    ) -> Score {
+        let (alpha, beta) = P::pvs_window(args.alpha, args.beta);
+        let mut null_args = AlphaBetaArgs {
+            alpha,
+            beta,
+            ..*args
+        };
 
-        let king_moves = board.king_legals(!better).len();
-        // dbg!(king_moves);
+        let reduce = self.config.late_move_reductions
+            && quiet
+            && searched >= LMR_MIN_MOVES
+            // `args` are for the child, so this is the depth of the current node
+            && args.remaining_depth + 1 >= LMR_MIN_DEPTH;
 
-        let dist = chess_lookup::distance(better_king, worse_king);
+        if reduce {
+            let reduction = if searched >= 2 * LMR_MIN_MOVES { 2 } else { 1 };
+            null_args.remaining_depth = args.remaining_depth.saturating_sub(reduction);
+        }
 
-        let mut penalty = 0;
-        // penalize for staying far away from the king
-        penalty += (dist as i32) * (dist as i32) * 100;
-        // penalize for staying far from the edge
-        penalty += DIST_FROM_EDGE[worse_king] as i32 * 10;
-        // penalize for allowing more kings moves
-        penalty += king_moves as i32 * 1000;
+        let mut new = self.alphabeta::<P::Flip>(mv, &null_args);
+        let improves = |new| P::is_better(P::own_bound(args.alpha, args.beta), new);
 
-        penalty
-    }
+        if reduce && improves(new) {
+            null_args.remaining_depth = args.remaining_depth;
+            new = self.alphabeta::<P::Flip>(mv, &null_args);
+        }
 
-    fn score_pieces(&mut self, board: &Board, color: Color) -> i32 {
-        let my_pieces = board[color];
+        if improves(new) && !P::fails_high(new, args.alpha, args.beta) {
+            new = self.alphabeta::<P::Flip>(mv, args);
+        }
 
-        let my_queen_score = (my_pieces & board[Piece::Queen]).count() as i32 * 900;
-        let my_rook_score = (my_pieces & board[Piece::Rook]).count() as i32 * 500;
-        let my_bishop_score = (my_pieces & board[Piece::Bishop]).count() as i32 * 330;
-        let my_knight_score = (my_pieces & board[Piece::Knight]).count() as i32 * 320;
-        let my_pawn_score = (my_pieces & board[Piece::Pawn]).count() as i32 * 100;
+        new
+    }
 
-        let mut position_score = 0i32;
+    /// Like [`Engine::quiescence`], but checks `board` for the end of the game first
+    fn quiescence_move<P: Policy>(
+        &mut self,
+        board: &Board,
+        ply: u16,
+        alpha: Score,
+        beta: Score,
+        timeout: impl TimeoutRef,
+    ) -> Score {
+        if board.has_insufficient_material() {
+            return Score::Raw(0);
+        }
 
-        if self.positional {
-            for queen in my_pieces & board[Piece::Queen] {
-                position_score += i32::from(QUEEN_MID_GAME_MAP[queen])
-            }
+        let moves = board.legals();
 
-            for knight in my_pieces & board[Piece::Knight] {
-                position_score += i32::from(KNIGHT_EARLY_GAME_MAP[knight])
-            }
+        if moves.is_empty() {
+            return if !board.in_check() {
+                Score::Raw(0)
+            } else {
+                match P::COLOR {
+                    Color::White => Score::BlackMateIn(ply),
+                    Color::Black => Score::WhiteMateIn(ply),
+                }
+            };
+        }
 
-            match color {
-                Color::White => {
-                    for rook in my_pieces & board[Piece::Rook] {
-                        position_score += i32::from(WHITE_ROOK_MID_GAME_MAP[rook])
-                    }
+        self.quiescence::<P>(board, moves, ply, alpha, beta, timeout)
+    }
 
-                    for bishop in my_pieces & board[Piece::Bishop] {
-                        position_score += i32::from(WHITE_BISHOP_MID_GAME_MAP[bishop])
-                    }
+    /// Whether both sides have enough material for pruning based on the static evaluation.
+    ///
+    /// Once either side has less than a queen and two rooks, less a pawn, zugzwang and mating
+    /// attacks against a bare king become likely
+    fn is_middlegame(&mut self, board: &Board) -> bool {
+        let endgame_material = self.piece_value(Piece::Queen) - self.piece_value(Piece::Pawn)
+            + 2 * self.piece_value(Piece::Rook);
 
-                    for pawn in my_pieces & board[Piece::Pawn] {
-                        position_score += i32::from(WHITE_PAWN_MID_GAME_MAP[pawn])
-                    }
-                }
-                Color::Black => {
-                    for rook in my_pieces & board[Piece::Rook].flip_ranks() {
-                        position_score += i32::from(WHITE_ROOK_MID_GAME_MAP[rook])
-                    }
+        eval::material(board, &self.params, Color::White).min(eval::material(
+            board,
+            &self.params,
+            Color::Black,
+        )) >= endgame_material
+    }
 
-                    for bishop in my_pieces & board[Piece::Bishop].flip_ranks() {
-                        position_score += i32::from(WHITE_BISHOP_MID_GAME_MAP[bishop])
-                    }
+    /// The middlegame value of `piece` in the evaluation
+    #[inline]
+    fn piece_value(&self, piece: Piece) -> i32 {
+        self.params.piece_values[piece].middlegame
+    }
 
-                    for pawn in my_pieces & board[Piece::Pawn].flip_ranks() {
-                        position_score += i32::from(WHITE_PAWN_MID_GAME_MAP[pawn])
-                    }
-                }
+    fn eval(&mut self, board: &Board, current_depth: u16) -> Score {
+        self.moves_evaluated += 1;
+
+        if self.helper && self.moves_evaluated.is_multiple_of(HELPER_NODE_BATCH) {
+            if let Some(shared) = &self.shared {
+                shared
+                    .helper_nodes
+                    .fetch_add(HELPER_NODE_BATCH, Ordering::Relaxed);
             }
         }
 
-        my_queen_score
-            + my_rook_score
-            + my_bishop_score
-            + my_knight_score
-            + my_pawn_score
-            + position_score
-    }
-
-    fn insuffient_material(&self, board: &Board) -> bool {
-        let board = board.raw();
-
-        let queens_or_rooks_or_pawns =
-            board[Piece::Queen] | board[Piece::Rook] | board[Piece::Pawn];
-
-        if queens_or_rooks_or_pawns.any() {
-            return false;
-        }
-
-        let bishops = board[Piece::Bishop].count();
-        let knights = board[Piece::Knight].count();
-
-        knights <= 1 && bishops == 0 || knights == 0 && bishops <= 1
-    }
-}
-
-#[rustfmt::skip]
-const KNIGHT_EARLY_GAME_MAP: [i8; 64] = transpose([
-    -50,-40,-30,-30,-30,-30,-40,-50,
-    -40,-20,  0,  0,  0,  0,-20,-40,
-    -30,  0, 10, 15, 15, 10,  0,-30,
-    -30,  5, 15, 20, 20, 15,  5,-30,
-    -30,  0, 15, 20, 20, 15,  0,-30,
-    -30,  5, 10, 15, 15, 10,  5,-30,
-    -40,-20,  0,  5,  5,  0,-20,-40,
-    -50,-40,-30,-30,-30,-30,-40,-50,
-]);
-
-#[rustfmt::skip]
-const WHITE_PAWN_MID_GAME_MAP: [i8; 64] = transpose([
-    0,  0,  0,  0,  0,  0,  0,  0,
-    50, 50, 50, 50, 50, 50, 50, 50,
-    10, 10, 20, 30, 30, 20, 10, 10,
-    5,  5, 10, 25, 25, 10,  5,  5,
-    0,  0,  0, 20, 20,  0,  0,  0,
-    5, -5,-10,  0,  0,-10, -5,  5,
-    5, 10, 10,-20,-20, 10, 10,  5,
-    0,  0,  0,  0,  0,  0,  0,  0
-]);
-
-#[rustfmt::skip]
-const QUEEN_MID_GAME_MAP: [i8; 64] = transpose([
-    -20,-10,-10, -5, -5,-10,-10,-20,
-    -10,  0,  0,  0,  0,  0,  0,-10,
-    -10,  0,  5,  5,  5,  5,  0,-10,
-     -5,  0,  5,  5,  5,  5,  0, -5,
-     -5,  0,  5,  5,  5,  5,  0, -5,
-    -10,  0,  5,  5,  5,  5,  0,-10,
-    -10,  0,  0,  0,  0,  0,  0,-10,
-    -20,-10,-10, -5, -5,-10,-10,-20
-]);
-
-#[rustfmt::skip]
-const WHITE_BISHOP_MID_GAME_MAP: [i8; 64] = transpose([
-    -20,-10,-10,-10,-10,-10,-10,-20,
-    -10,  0,  0,  0,  0,  0,  0,-10,
-    -10,  0,  5, 10, 10,  5,  0,-10,
-    -10,  5,  5, 10, 10,  5,  5,-10,
-    -10,  0, 10, 10, 10, 10,  0,-10,
-    -10, 10, 10, 10, 10, 10, 10,-10,
-    -10,  5,  0,  0,  0,  0,  5,-10,
-    -20,-10,-10,-10,-10,-10,-10,-20,
-]);
-
-#[rustfmt::skip]
-const WHITE_ROOK_MID_GAME_MAP: [i8; 64] = transpose([
-    0,  0,  0,  0,  0,  0,  0,  0,
-    5, 10, 10, 10, 10, 10, 10,  5,
-   -5,  0,  0,  0,  0,  0,  0, -5,
-   -5,  0,  0,  0,  0,  0,  0, -5,
-   -5,  0,  0,  0,  0,  0,  0, -5,
-   -5,  0,  0,  0,  0,  0,  0, -5,
-   -5,  0,  0,  0,  0,  0,  0, -5,
-    0,  0,  0,  5,  5,  0,  0,  0
-]);
-
-#[rustfmt::skip]
-const WHITE_KING_EARLY_GAME_MAP: [i8; 64] = transpose([
-    -30,-40,-40,-50,-50,-40,-40,-30,
-    -30,-40,-40,-50,-50,-40,-40,-30,
-    -30,-40,-40,-50,-50,-40,-40,-30,
-    -30,-40,-40,-50,-50,-40,-40,-30,
-    -20,-30,-30,-40,-40,-30,-30,-20,
-    -10,-20,-20,-20,-20,-20,-20,-10,
-     20, 20,  0,  0,  0,  0, 20, 20,
-     20, 30, 10,  0,  0, 10, 30, 20
-]);
-
-#[rustfmt::skip]
-const WHITE_KING_END_GAME_MAP: [i8; 64] = transpose([
-    -50,-40,-30,-20,-20,-30,-40,-50,
-    -30,-20,-10,  0,  0,-10,-20,-30,
-    -30,-10, 20, 30, 30, 20,-10,-30,
-    -30,-10, 30, 40, 40, 30,-10,-30,
-    -30,-10, 30, 40, 40, 30,-10,-30,
-    -30,-10, 20, 30, 30, 20,-10,-30,
-    -30,-30,  0,  0,  0,  0,-30,-30,
-    -50,-30,-30,-30,-30,-30,-30,-50
-]);
-
-const fn transpose<T: Copy>(input: [T; 64]) -> [T; 64] {
-    let mut output = input;
-
-    let mut i = 0;
-
-    while i < 64 {
-        let x = i % 8;
-        let y = i / 8;
-
-        output[i] = input[(7 - x) * 8 + y];
-
-        i += 1;
-    }
-
-    output
-}
-
-static DIST_FROM_EDGE: [u8; 64] = {
-    use chess_bitboard::{File, Pos, Rank};
-    let mut scores = [0; 64];
-
-    let mut i = 0;
-
-    while i < scores.len() {
-        let Some(pos) = Pos::from_u8(i as u8) else {
-            unreachable!()
-        };
+        if board.half_move_clock() >= 100 {
+            return Score::Raw(0);
+        }
 
-        let file = pos.file();
-        let rank = pos.rank();
+        let score = match &self.network {
+            Some(network) => network.evaluate(&self.accumulators, current_depth, board.turn()),
+            None => eval::evaluate(board, &self.params, self.positional, Some(&mut self.pawns)),
+        };
 
-        let to_a = file.dist_to(File::A);
-        let to_h = file.dist_to(File::H);
+        tracing::trace!(current_depth, score);
 
-        let to_1 = rank.dist_to(Rank::_1);
-        let to_8 = rank.dist_to(Rank::_8);
+        Score::Raw(score)
+    }
 
-        let to_file_edge = if to_a < to_h { to_a } else { to_h };
-        let to_rank_edge = if to_1 < to_8 { to_1 } else { to_8 };
+    /// Make `mv` on `board`, updating the accumulators of the network for `ply`
+    ///
+    /// # Safety
+    ///
+    /// The same as [`Board::move_unchecked`]
+    #[inline]
+    unsafe fn make_move(&mut self, board: &Board, mv: ChessMove, ply: u16) -> Board {
+        let Some(network) = &self.network else {
+            return board.move_unchecked(mv);
+        };
 
-        scores[i] = to_file_edge * to_rank_edge * 10
-            + to_file_edge * to_file_edge
-            + to_rank_edge * to_rank_edge;
+        let mut new_board = *board;
+        let mut changes = nnue::FeatureChanges::default();
+        board.move_unchecked_observed(mv, &mut new_board, |color, piece, diff| {
+            changes.xor(color, piece, diff)
+        });
+        self.accumulators.update(network, ply, &new_board, &changes);
 
-        i += 1;
+        new_board
     }
+}
 
-    scores
-};
+/// Captures which can't bring the score within this margin of alpha are skipped in quiescence
+const DELTA_MARGIN: i32 = 200;
 
 #[test]
 #[ignore]
 fn test() {
+    let params = EvalParams::default();
+
     for rank in chess_bitboard::Rank::all().rev() {
         for pos in rank {
-            print!("{:2} ", WHITE_BISHOP_MID_GAME_MAP[pos]);
+            print!("{:2} ", params.middlegame_tables[Piece::Bishop][pos]);
         }
 
         println!();
@@ -914,3 +1564,208 @@ fn test() {
 
     panic!()
 }
+
+#[test]
+fn test_search_reports_principal_variation() {
+    let board: Board = "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1".parse().unwrap();
+    let mut engine = Engine::default();
+
+    let mut depths = Vec::new();
+    let (mv, score) = engine.search_limited(
+        &board,
+        &ThreeFold::new(),
+        SearchLimits::depth(3),
+        (),
+        |info: &SearchInfo| {
+            assert!(!info.pv.is_empty());
+            depths.push(info.depth);
+        },
+    );
+
+    assert_eq!(score, Score::WhiteMateIn(1));
+    assert_eq!(engine.principal_variation().first().copied(), mv);
+    assert!(!depths.is_empty());
+    assert!(depths.windows(2).all(|w| w[0] < w[1]));
+
+    let mut board = board;
+    for &mv in engine.principal_variation() {
+        assert!(board.move_mut(mv));
+    }
+}
+
+#[test]
+fn test_quiescence_sees_recaptures() {
+    // taking the pawn on e5 loses the queen to d6xe5
+    let board: Board = "rn1qk2r/p6p/3p4/4p3/8/8/P2PQ2P/RN1QK2R w - - 0 1"
+        .parse()
+        .unwrap();
+    let mut engine = Engine::default();
+
+    let (mv, score) =
+        engine.search_limited(&board, &ThreeFold::new(), SearchLimits::depth(1), (), ());
+
+    let mv = mv.unwrap();
+    assert_ne!(
+        (mv.source, mv.dest),
+        (chess_bitboard::Pos::E2, chess_bitboard::Pos::E5)
+    );
+    assert!(score > Score::Raw(0));
+}
+
+#[test]
+fn test_quiescence_searches_en_passant() {
+    // taking en passant is the only capture, so without it quiescence only evaluates the
+    // position it starts from
+    let board: Board = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1".parse().unwrap();
+    let mut engine = Engine::default();
+    let timeout = DurationTimeout::new(Duration::from_secs(60));
+
+    let _ = engine.quiescence_move::<White>(&board, 0, Score::Min, Score::Max, timeout);
+    assert_eq!(engine.moves_evaluated, 2);
+}
+
+#[test]
+fn test_quiescence_terminates_in_tactical_positions() {
+    // kiwipete has a lot of captures available for both sides
+    let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+        .parse()
+        .unwrap();
+    let mut engine = Engine::default();
+
+    let (mv, _) = engine.search_limited(&board, &ThreeFold::new(), SearchLimits::depth(3), (), ());
+
+    assert!(mv.is_some());
+    assert_eq!(engine.max_depth, 2);
+}
+
+#[test]
+fn test_selective_search_finds_mates() {
+    let board: Board = "6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1"
+        .parse()
+        .unwrap();
+
+    let all = SearchConfig::default();
+    let none = SearchConfig {
+        null_move_pruning: false,
+        late_move_reductions: false,
+        futility_pruning: false,
+        check_extensions: false,
+    };
+
+    for config in [all, none] {
+        let mut engine = Engine {
+            config,
+            ..Engine::default()
+        };
+
+        let (mv, score) =
+            engine.search_limited(&board, &ThreeFold::new(), SearchLimits::depth(4), (), ());
+
+        let mv = mv.unwrap();
+        assert_eq!((mv.source, mv.dest), (Pos::G2, Pos::G1), "{config:?}");
+        assert_eq!(score, Score::BlackMateIn(3), "{config:?}");
+    }
+}
+
+#[test]
+fn test_multi_threaded_search_finds_mates() {
+    let board: Board = "6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1"
+        .parse()
+        .unwrap();
+
+    let mut engine = Engine {
+        threads: 4,
+        ..Engine::default()
+    };
+
+    let mut nodes = Vec::new();
+    let (mv, score) = engine.search_limited(
+        &board,
+        &ThreeFold::new(),
+        SearchLimits::depth(4),
+        (),
+        |info: &SearchInfo| nodes.push(info.nodes),
+    );
+
+    let mv = mv.unwrap();
+    assert_eq!((mv.source, mv.dest), (Pos::G2, Pos::G1));
+    assert_eq!(score, Score::BlackMateIn(3));
+
+    // the helper threads' nodes are added to the total once they have stopped
+    assert!(engine.moves_evaluated >= *nodes.last().unwrap());
+}
+
+#[test]
+fn test_node_limited_searches_are_deterministic() {
+    let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+        .parse()
+        .unwrap();
+
+    let search = || {
+        let mut engine = Engine::default();
+        let result = engine.search_limited(
+            &board,
+            &ThreeFold::new(),
+            SearchLimits::nodes(20_000),
+            (),
+            (),
+        );
+        (result, engine.moves_evaluated, engine.max_depth)
+    };
+
+    let ((mv, score), nodes, depth) = search();
+    assert!(mv.is_some());
+    // the limit is checked between moves, so it may be overshot by a little
+    assert!((20_000..21_000).contains(&nodes), "{nodes}");
+    assert_eq!(search(), ((mv, score), nodes, depth));
+}
+
+#[test]
+fn test_mate_limit() {
+    // there is no mate to find, so the search stops after the 3 plies a mate in 2 needs
+    let mut engine = Engine::default();
+    let (mv, _) = engine.search_limited(
+        &Board::standard(),
+        &ThreeFold::new(),
+        SearchLimits::mate(2),
+        (),
+        (),
+    );
+    assert!(mv.is_some());
+    assert_eq!(engine.max_depth, 2);
+
+    let board: Board = "6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1"
+        .parse()
+        .unwrap();
+    let (mv, score) =
+        engine.search_limited(&board, &ThreeFold::new(), SearchLimits::mate(2), (), ());
+    let mv = mv.unwrap();
+    assert_eq!((mv.source, mv.dest), (Pos::G2, Pos::G1));
+    assert_eq!(score, Score::BlackMateIn(3));
+}
+
+#[test]
+fn test_search_with_network() {
+    let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+        .parse()
+        .unwrap();
+
+    let mut engine = Engine::default();
+    engine.set_network(Some(Network::random(16)));
+    let (mv, _) = engine.search_limited(
+        &board,
+        &ThreeFold::new(),
+        SearchLimits::nodes(5_000),
+        (),
+        (),
+    );
+    assert!(board.is_legal(mv.unwrap()));
+
+    // the network doesn't change how mates are found
+    let board: Board = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".parse().unwrap();
+    let (mv, score) =
+        engine.search_limited(&board, &ThreeFold::new(), SearchLimits::mate(1), (), ());
+    let mv = mv.unwrap();
+    assert_eq!((mv.source, mv.dest), (Pos::A1, Pos::A8));
+    assert_eq!(score, Score::WhiteMateIn(1));
+}
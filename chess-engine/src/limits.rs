@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+/// Bounds on a search, which stops as soon as any of them is reached.
+///
+/// The default has no limits, so the search runs until it finds a mate or its timeout completes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchLimits {
+    /// Stop searching after completing this many plies
+    pub depth: Option<u16>,
+    /// Stop searching after evaluating this many positions, which makes the search deterministic
+    /// when it is single threaded
+    pub nodes: Option<u64>,
+    /// Stop searching after this much time has passed
+    pub move_time: Option<Duration>,
+    /// Only search deep enough to find a mate in this many moves for the side to move
+    pub mate: Option<u16>,
+    /// Keep searching after finding a mate, until one of the other limits is reached or the
+    /// timeout completes
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    pub const fn depth(depth: u16) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::none()
+        }
+    }
+
+    pub const fn nodes(nodes: u64) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..Self::none()
+        }
+    }
+
+    pub const fn move_time(move_time: Duration) -> Self {
+        Self {
+            move_time: Some(move_time),
+            ..Self::none()
+        }
+    }
+
+    pub const fn mate(moves: u16) -> Self {
+        Self {
+            mate: Some(moves),
+            ..Self::none()
+        }
+    }
+
+    pub const fn infinite() -> Self {
+        Self {
+            infinite: true,
+            ..Self::none()
+        }
+    }
+
+    const fn none() -> Self {
+        Self {
+            depth: None,
+            nodes: None,
+            move_time: None,
+            mate: None,
+            infinite: false,
+        }
+    }
+
+    /// The number of plies to search, a mate in `n` moves takes `2n - 1` plies to find
+    pub(crate) fn max_depth(&self) -> Option<u16> {
+        let mate_depth = self
+            .mate
+            .map(|moves| moves.saturating_mul(2).saturating_sub(1));
+
+        match (self.depth, mate_depth) {
+            (Some(depth), Some(mate_depth)) => Some(depth.min(mate_depth)),
+            (depth, mate_depth) => depth.or(mate_depth),
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use chess_bitboard::{BitBoard, Color, Piece, Pos};
+use chess_movegen::Board;
+
+/// Every file starts with these bytes
+const MAGIC: [u8; 4] = *b"NNUE";
+
+/// One input for every color, kind of piece and square
+const FEATURES: usize = 2 * 6 * 64;
+
+/// Hidden values are clipped to `0..=QA`
+const QA: i32 = 255;
+/// The output weights are scaled by this much
+const QB: i32 = 64;
+/// Converts the output of the network to centipawns
+const SCALE: i32 = 400;
+
+/// An error from loading a [`Network`]
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(std::io::Error),
+    InvalidMagic,
+    /// The file doesn't have the number of weights its hidden size needs
+    InvalidSize {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl core::fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "{err}"),
+            NetworkError::InvalidMagic => write!(f, "not a network file, it must start with NNUE"),
+            NetworkError::InvalidSize { expected, found } => {
+                write!(f, "expected {expected} bytes of weights, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+/// An efficiently updatable neural network, which replaces the classical evaluation.
+///
+/// The inputs are the 768 combinations of color, kind of piece and square, seen from the
+/// perspective of both sides. Each perspective has an accumulator of `hidden` values which is
+/// updated as pieces are added and removed, so a move only changes a few inputs. Both
+/// accumulators go through a clipped ReLU into a single output, with the side to move first.
+///
+/// A network file is `NNUE`, the hidden size as a little endian `u32`, then little endian
+/// `i16`s: the feature weights (`768 * hidden`, one row per feature), the feature biases
+/// (`hidden`), the output weights (`2 * hidden`) and the output bias
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    hidden: usize,
+    feature_weights: Box<[i16]>,
+    feature_biases: Box<[i16]>,
+    output_weights: Box<[i16]>,
+    output_bias: i16,
+}
+
+impl Network {
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, NetworkError> {
+        let bytes = std::fs::read(path).map_err(NetworkError::Io)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkError> {
+        let (magic, bytes) = bytes
+            .split_at_checked(4)
+            .ok_or(NetworkError::InvalidMagic)?;
+        if magic != MAGIC {
+            return Err(NetworkError::InvalidMagic);
+        }
+
+        let (hidden, bytes) = bytes.split_at_checked(4).ok_or(NetworkError::InvalidSize {
+            expected: 4,
+            found: bytes.len(),
+        })?;
+        let hidden = u32::from_le_bytes(hidden.try_into().unwrap()) as usize;
+
+        let weights = FEATURES * hidden + hidden + 2 * hidden + 1;
+        if bytes.len() != weights * 2 {
+            return Err(NetworkError::InvalidSize {
+                expected: weights * 2,
+                found: bytes.len(),
+            });
+        }
+
+        let mut values = bytes
+            .chunks_exact(2)
+            .map(|value| i16::from_le_bytes([value[0], value[1]]));
+        let mut take = |len| values.by_ref().take(len).collect::<Box<[i16]>>();
+
+        Ok(Self {
+            hidden,
+            feature_weights: take(FEATURES * hidden),
+            feature_biases: take(hidden),
+            output_weights: take(2 * hidden),
+            output_bias: take(1)[0],
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend((self.hidden as u32).to_le_bytes());
+
+        for value in self
+            .feature_weights
+            .iter()
+            .chain(&*self.feature_biases)
+            .chain(&*self.output_weights)
+            .chain([&self.output_bias])
+        {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// The number of values in each accumulator
+    #[inline]
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    #[inline]
+    fn weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..][..self.hidden]
+    }
+
+    /// The evaluation of the position in `accumulators` at `ply` in centipawns from
+    /// white's point of view
+    pub(crate) fn evaluate(&self, accumulators: &Accumulators, ply: u16, turn: Color) -> i32 {
+        let values = accumulators.get(self, ply);
+        let (white, black) = values.split_at(self.hidden);
+        let (us, them) = match turn {
+            Color::White => (white, black),
+            Color::Black => (black, white),
+        };
+
+        let (us_weights, them_weights) = self.output_weights.split_at(self.hidden);
+
+        let mut output = 0;
+        for (values, weights) in [(us, us_weights), (them, them_weights)] {
+            for (&value, &weight) in values.iter().zip(weights) {
+                output += i32::from(value).clamp(0, QA) * i32::from(weight);
+            }
+        }
+
+        let score = (output / QA + i32::from(self.output_bias)) * SCALE / (QA * QB);
+
+        match turn {
+            Color::White => score,
+            Color::Black => -score,
+        }
+    }
+}
+
+/// The input for a piece as seen by `perspective`, black sees the board flipped
+#[inline]
+fn feature(perspective: Color, color: Color, piece: Piece, pos: Pos) -> usize {
+    let (side, pos) = match perspective {
+        Color::White => (color, pos),
+        Color::Black => (!color, pos.flip_rank()),
+    };
+
+    (side as usize * 6 + piece as usize) * 64 + pos as usize
+}
+
+/// The net change to the pieces in a move, collected from
+/// [`Board::move_unchecked_observed`]
+pub(crate) struct FeatureChanges([[BitBoard; 6]; 2]);
+
+impl Default for FeatureChanges {
+    fn default() -> Self {
+        Self([[BitBoard::empty(); 6]; 2])
+    }
+}
+
+impl FeatureChanges {
+    #[inline]
+    pub(crate) fn xor(&mut self, color: Color, piece: Piece, diff: BitBoard) {
+        self.0[color][piece] ^= diff;
+    }
+}
+
+/// The accumulators of both perspectives for every ply of the search
+#[derive(Default)]
+pub(crate) struct Accumulators {
+    /// `2 * hidden` values per ply, white's perspective first
+    values: Vec<i16>,
+}
+
+impl Accumulators {
+    #[inline]
+    fn get(&self, network: &Network, ply: u16) -> &[i16] {
+        let len = 2 * network.hidden;
+        &self.values[usize::from(ply) * len..][..len]
+    }
+
+    /// Make room for `ply`, and return the previous ply and `ply`
+    #[inline]
+    fn parent_and_child(&mut self, network: &Network, ply: u16) -> (&[i16], &mut [i16]) {
+        let len = 2 * network.hidden;
+        let ply = usize::from(ply);
+
+        if self.values.len() < (ply + 1) * len {
+            self.values.resize((ply + 1) * len, 0);
+        }
+
+        let (parent, child) = self.values[(ply - 1) * len..][..2 * len].split_at_mut(len);
+        (parent, child)
+    }
+
+    /// Compute the accumulators of `board` at `ply` from scratch
+    pub(crate) fn refresh(&mut self, network: &Network, ply: u16, board: &Board) {
+        let len = 2 * network.hidden;
+        let ply = usize::from(ply);
+
+        if self.values.len() < (ply + 1) * len {
+            self.values.resize((ply + 1) * len, 0);
+        }
+
+        let values = &mut self.values[ply * len..][..len];
+        let (white, black) = values.split_at_mut(network.hidden);
+        white.copy_from_slice(&network.feature_biases);
+        black.copy_from_slice(&network.feature_biases);
+
+        for color in Color::all() {
+            for piece in Piece::all() {
+                for pos in board[color] & board[piece] {
+                    add(
+                        white,
+                        network.weights(feature(Color::White, color, piece, pos)),
+                    );
+                    add(
+                        black,
+                        network.weights(feature(Color::Black, color, piece, pos)),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The position at `ply` is the one at the previous ply after a null move
+    pub(crate) fn copy(&mut self, network: &Network, ply: u16) {
+        let (parent, child) = self.parent_and_child(network, ply);
+        child.copy_from_slice(parent);
+    }
+
+    /// The position at `ply` is `board`, which `changes` made from the one at the previous ply
+    pub(crate) fn update(
+        &mut self,
+        network: &Network,
+        ply: u16,
+        board: &Board,
+        changes: &FeatureChanges,
+    ) {
+        let (parent, child) = self.parent_and_child(network, ply);
+        child.copy_from_slice(parent);
+
+        let (white, black) = child.split_at_mut(network.hidden);
+
+        for color in Color::all() {
+            for piece in Piece::all() {
+                let now = board[color] & board[piece];
+
+                for pos in changes.0[color][piece] {
+                    let update = if now.contains(pos) { add } else { sub };
+                    update(
+                        white,
+                        network.weights(feature(Color::White, color, piece, pos)),
+                    );
+                    update(
+                        black,
+                        network.weights(feature(Color::Black, color, piece, pos)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn add(values: &mut [i16], weights: &[i16]) {
+    for (value, &weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_add(weight);
+    }
+}
+
+#[inline]
+fn sub(values: &mut [i16], weights: &[i16]) {
+    for (value, &weight) in values.iter_mut().zip(weights) {
+        *value = value.wrapping_sub(weight);
+    }
+}
+
+#[cfg(test)]
+impl Network {
+    /// A network with small pseudo random weights
+    pub(crate) fn random(hidden: usize) -> Self {
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |len: usize| {
+            (0..len)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    (state % 64) as i16 - 32
+                })
+                .collect::<Box<[i16]>>()
+        };
+
+        Self {
+            hidden,
+            feature_weights: next(FEATURES * hidden),
+            feature_biases: next(hidden),
+            output_weights: next(2 * hidden),
+            output_bias: next(1)[0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_bytes() {
+        let network = Network::random(8);
+        let bytes = network.to_bytes();
+        assert_eq!(Network::from_bytes(&bytes).unwrap(), network);
+
+        assert!(matches!(
+            Network::from_bytes(b"NNUF\x08\0\0\0"),
+            Err(NetworkError::InvalidMagic)
+        ));
+        assert!(matches!(
+            Network::from_bytes(&bytes[..bytes.len() - 2]),
+            Err(NetworkError::InvalidSize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_incremental_updates_match_refresh() {
+        let network = Network::random(16);
+        let mut incremental = Accumulators::default();
+        let mut refreshed = Accumulators::default();
+
+        // castling, en passant and promotions with and without a capture
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/1P6/8/3pP3/8/8/6p1/R3K2R w KQkq d6 0 1",
+        ] {
+            let board: Board = fen.parse().unwrap();
+            incremental.refresh(&network, 0, &board);
+
+            for mv in board.legals() {
+                let mut child = board;
+                let mut changes = FeatureChanges::default();
+                unsafe {
+                    board.move_unchecked_observed(mv, &mut child, |color, piece, diff| {
+                        changes.xor(color, piece, diff)
+                    })
+                };
+                incremental.update(&network, 1, &child, &changes);
+                refreshed.refresh(&network, 1, &child);
+                assert_eq!(
+                    incremental.get(&network, 1),
+                    refreshed.get(&network, 1),
+                    "{mv} in {fen}"
+                );
+                assert_eq!(
+                    network.evaluate(&incremental, 1, child.turn()),
+                    network.evaluate(&refreshed, 1, child.turn()),
+                );
+            }
+        }
+    }
+}
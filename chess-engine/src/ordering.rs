@@ -0,0 +1,286 @@
+use arrayvec::ArrayVec;
+use chess_bitboard::{Piece, PromotionPiece};
+use chess_movegen::{Board, ChessMove};
+
+const TT_MOVE: i32 = 1 << 30;
+const GOOD_PROMOTION: i32 = 1 << 26;
+const CAPTURE: i32 = 1 << 25;
+const KILLER: i32 = 1 << 24;
+const COUNTER_MOVE: i32 = KILLER - 2;
+/// Captures which lose material are tried after the quiet moves
+const LOSING_CAPTURE: i32 = -(1 << 24);
+const UNDER_PROMOTION: i32 = -(1 << 25);
+
+/// History scores are kept below this so quiet moves are never tried before killers
+const MAX_HISTORY: i32 = 1 << 20;
+
+/// The maximum number of legal moves in any chess position
+const MAX_MOVES: usize = 218;
+
+type ScoredMoves = ArrayVec<(i32, ChessMove), MAX_MOVES>;
+
+/// Quiet moves which caused cutoffs earlier in the search, used to order moves
+/// in positions which haven't been searched yet
+pub(crate) struct Heuristics {
+    /// Two quiet moves per ply which recently caused a cutoff at that ply
+    killers: Vec<[Option<ChessMove>; 2]>,
+    /// How often a quiet move from a square to a square caused a cutoff, weighted by depth
+    history: Box<[[[i32; 64]; 64]; 2]>,
+    /// The quiet move which refuted the previous move, indexed by its squares
+    counter_moves: Box<[[Option<ChessMove>; 64]; 64]>,
+}
+
+impl Default for Heuristics {
+    fn default() -> Self {
+        Self {
+            killers: Vec::new(),
+            history: Box::new([[[0; 64]; 64]; 2]),
+            counter_moves: Box::new([[None; 64]; 64]),
+        }
+    }
+}
+
+impl Heuristics {
+    /// Killers only make sense in the search that found them, but the
+    /// history is still a good guess so it is only made less important
+    pub fn new_search(&mut self) {
+        self.killers.clear();
+        self.decay();
+    }
+
+    fn decay(&mut self) {
+        self.history
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .for_each(|score| *score /= 2);
+    }
+
+    #[inline]
+    fn killers(&self, ply: u16) -> [Option<ChessMove>; 2] {
+        self.killers
+            .get(usize::from(ply))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// `mv` caused a beta cutoff at `ply`, with `depth` plies remaining
+    pub fn record_cutoff(
+        &mut self,
+        board: &Board,
+        prev: Option<ChessMove>,
+        mv: ChessMove,
+        ply: u16,
+        depth: u16,
+    ) {
+        if !is_quiet(board, mv) {
+            return;
+        }
+
+        let ply = usize::from(ply);
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        let history = &mut self.history[board.turn()][mv.source][mv.dest];
+        *history += i32::from(depth) * i32::from(depth);
+        if *history >= MAX_HISTORY {
+            self.decay();
+        }
+
+        if let Some(prev) = prev {
+            self.counter_moves[prev.source][prev.dest] = Some(mv);
+        }
+    }
+}
+
+/// Yields moves from best to worst according to how likely they are to cause a cutoff.
+///
+/// Moves are sorted lazily, so if an early move causes a cutoff the rest are never sorted
+pub(crate) struct MovePicker {
+    moves: ScoredMoves,
+    index: usize,
+}
+
+impl MovePicker {
+    /// Order all of `moves`: the `tt_move` first, then promotions to a queen, captures which
+    /// don't lose material by MVV-LVA, killers, the counter move to `prev`, quiet moves by
+    /// history, losing captures, and under promotions
+    pub fn new(
+        board: &Board,
+        moves: impl IntoIterator<Item = ChessMove>,
+        tt_move: Option<ChessMove>,
+        prev: Option<ChessMove>,
+        ply: u16,
+        heuristics: &Heuristics,
+    ) -> Self {
+        let killers = heuristics.killers(ply);
+        let counter_move = prev.and_then(|prev| heuristics.counter_moves[prev.source][prev.dest]);
+        let history = &heuristics.history[board.turn()];
+
+        let moves = moves
+            .into_iter()
+            .map(|mv| {
+                let score = if Some(mv) == tt_move {
+                    TT_MOVE
+                } else {
+                    match mv.piece {
+                        Some(PromotionPiece::Queen) => GOOD_PROMOTION + mvv_lva(board, mv),
+                        Some(_) => UNDER_PROMOTION,
+                        None if !is_quiet(board, mv) => {
+                            let score = if board.see_ge(mv, 0) {
+                                CAPTURE
+                            } else {
+                                LOSING_CAPTURE
+                            };
+                            score + mvv_lva(board, mv)
+                        }
+                        None if Some(mv) == killers[0] => KILLER,
+                        None if Some(mv) == killers[1] => KILLER - 1,
+                        None if Some(mv) == counter_move => COUNTER_MOVE,
+                        None => history[mv.source][mv.dest],
+                    }
+                };
+
+                (score, mv)
+            })
+            .collect();
+
+        Self { moves, index: 0 }
+    }
+
+    /// Order only by MVV-LVA, for positions where killers and history don't apply
+    pub fn captures(board: &Board, moves: impl IntoIterator<Item = ChessMove>) -> Self {
+        let moves = moves
+            .into_iter()
+            .map(|mv| (mvv_lva(board, mv), mv))
+            .collect();
+
+        Self { moves, index: 0 }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = ChessMove;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = &mut self.moves[self.index..];
+
+        let (best, _) = remaining
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, &(score, _))| (score, core::cmp::Reverse(*i)))?;
+
+        remaining.swap(0, best);
+        self.index += 1;
+
+        Some(remaining[0].1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.moves.len() - self.index;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for MovePicker {}
+
+/// Only used to order captures, so they don't depend on the evaluation weights
+const fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Knight => 320,
+        Piece::Bishop => 330,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 0,
+    }
+}
+
+/// Capture the most valuable victim with the least valuable attacker first
+fn mvv_lva(board: &Board, mv: ChessMove) -> i32 {
+    let victim = board.captured_piece(mv).map_or(0, piece_value);
+    let attacker = board.raw().piece_of(mv.source).map_or(0, piece_value);
+
+    victim * 16 - attacker / 100
+}
+
+/// Neither a capture nor a promotion
+pub(crate) fn is_quiet(board: &Board, mv: ChessMove) -> bool {
+    let kind = board.move_kind(mv);
+    !kind.is_capture() && !kind.is_promotion()
+}
+
+#[cfg(test)]
+mod tests {
+    use chess_bitboard::Pos;
+
+    use super::*;
+
+    #[test]
+    fn test_picks_tt_move_then_captures() {
+        // white can take the queen with the pawn or the rook
+        let board: Board = "4k3/8/8/3q4/4P3/8/8/3RK3 w - - 0 1".parse().unwrap();
+        let tt_move = ChessMove {
+            source: Pos::E1,
+            dest: Pos::F2,
+            piece: None,
+        };
+
+        let heuristics = Heuristics::default();
+        let mut picker =
+            MovePicker::new(&board, board.legals(), Some(tt_move), None, 0, &heuristics);
+
+        assert_eq!(picker.len(), board.legals().len());
+        assert_eq!(picker.next(), Some(tt_move));
+
+        let capture = picker.next().unwrap();
+        assert_eq!((capture.source, capture.dest), (Pos::E4, Pos::D5));
+
+        let capture = picker.next().unwrap();
+        assert_eq!((capture.source, capture.dest), (Pos::D1, Pos::D5));
+    }
+
+    #[test]
+    fn test_losing_captures_come_after_quiet_moves() {
+        // the pawn on d5 is defended, so taking it loses the queen
+        let board: Board = "4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1".parse().unwrap();
+
+        let heuristics = Heuristics::default();
+        let picker = MovePicker::new(&board, board.legals(), None, None, 0, &heuristics);
+        let last = picker.last().unwrap();
+        assert_eq!((last.source, last.dest), (Pos::D1, Pos::D5));
+    }
+
+    #[test]
+    fn test_killers_and_history() {
+        let board = Board::standard();
+        let quiet = |source, dest| ChessMove {
+            source,
+            dest,
+            piece: None,
+        };
+        let knight = quiet(Pos::G1, Pos::F3);
+        let pawn = quiet(Pos::A2, Pos::A3);
+
+        let mut heuristics = Heuristics::default();
+        heuristics.record_cutoff(&board, None, knight, 3, 2);
+        heuristics.record_cutoff(&board, None, pawn, 3, 1);
+
+        // the most recent killer is tried first
+        let mut picker = MovePicker::new(&board, board.legals(), None, None, 3, &heuristics);
+        assert_eq!(picker.next(), Some(pawn));
+        assert_eq!(picker.next(), Some(knight));
+
+        // other plies only use the history, which prefers the deeper cutoff
+        let mut picker = MovePicker::new(&board, board.legals(), None, None, 4, &heuristics);
+        assert_eq!(picker.next(), Some(knight));
+        assert_eq!(picker.next(), Some(pawn));
+    }
+}
@@ -0,0 +1,569 @@
+use core::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub};
+
+use chess_bitboard::Pos;
+
+/// A pair of middlegame and endgame scores, which are interpolated by the game phase
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tapered {
+    pub middlegame: i32,
+    pub endgame: i32,
+}
+
+impl Tapered {
+    pub const fn new(middlegame: i32, endgame: i32) -> Self {
+        Self {
+            middlegame,
+            endgame,
+        }
+    }
+}
+
+impl Add for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.middlegame + rhs.middlegame, self.endgame + rhs.endgame)
+    }
+}
+
+impl AddAssign for Tapered {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Neg for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.middlegame, -self.endgame)
+    }
+}
+
+impl Mul<i32> for Tapered {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: i32) -> Self {
+        Self::new(self.middlegame * rhs, self.endgame * rhs)
+    }
+}
+
+/// A bonus for every square of the board from white's point of view, indexed by [`Pos`].
+///
+/// It is stored in files as 8 rows of 8 squares, with the 8th rank first
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "[[i32; 8]; 8]", into = "[[i32; 8]; 8]")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SquareTable([i32; 64]);
+
+impl From<[[i32; 8]; 8]> for SquareTable {
+    fn from(rows: [[i32; 8]; 8]) -> Self {
+        Self(flip(rows.concat().try_into().unwrap()))
+    }
+}
+
+impl From<SquareTable> for [[i32; 8]; 8] {
+    fn from(table: SquareTable) -> Self {
+        let squares = flip(table.0);
+        core::array::from_fn(|rank| squares[rank * 8..][..8].try_into().unwrap())
+    }
+}
+
+impl Index<Pos> for SquareTable {
+    type Output = i32;
+
+    #[inline]
+    fn index(&self, pos: Pos) -> &i32 {
+        &self.0[pos]
+    }
+}
+
+impl IndexMut<Pos> for SquareTable {
+    #[inline]
+    fn index_mut(&mut self, pos: Pos) -> &mut i32 {
+        &mut self.0[pos]
+    }
+}
+
+/// Every weight of the evaluation, the default is the hand tuned set the engine ships with.
+///
+/// With the `serde` feature they can be loaded from and saved to toml or json files, missing
+/// weights keep their default values
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    /// Indexed by [`Piece`](chess_bitboard::Piece)
+    pub piece_values: [Tapered; 6],
+    /// Piece square tables in the middlegame, indexed by [`Piece`](chess_bitboard::Piece)
+    pub middlegame_tables: [SquareTable; 6],
+    /// Piece square tables in the endgame, indexed by [`Piece`](chess_bitboard::Piece)
+    pub endgame_tables: [SquareTable; 6],
+    /// How much the stronger side gains for every square its king is closer to a lone enemy king
+    pub mop_up_king_distance: i32,
+
+    /// Bonus for a passed pawn, indexed by the rank relative to its side
+    pub passed: [Tapered; 8],
+    /// Bonus for a pawn which is defended by or next to another pawn, indexed by the rank
+    /// relative to its side
+    pub connected: [Tapered; 8],
+    /// Penalty for every pawn with another pawn of the same side in front of it
+    pub doubled: Tapered,
+    /// Penalty for a pawn without pawns of the same side on the adjacent files
+    pub isolated: Tapered,
+    /// Penalty for a pawn which can't be defended by other pawns and can't advance safely
+    pub backward: Tapered,
+
+    /// Bonus for every square a piece can move to, indexed by [`Piece`](chess_bitboard::Piece)
+    pub mobility: [Tapered; 6],
+    /// The number of squares a piece is expected to reach, fewer squares are a penalty
+    pub mobility_base: [i32; 6],
+    /// Attack units for every square of the enemy king zone a piece attacks
+    pub attack_units: [i32; 6],
+    /// Percentage of the attack units which count as a bonus for the attacking side, indexed by
+    /// the number of attacking pieces
+    pub attacker_scale: [i32; 8],
+    /// Bonus for every pawn directly in front of the king, or on the files next to it
+    pub shield_close: Tapered,
+    /// Bonus for every shield pawn which has advanced one more square
+    pub shield_far: Tapered,
+    /// Penalty for every file next to or at the king without pawns of its side
+    pub semi_open_file: Tapered,
+    /// Penalty for every file next to or at the king without any pawns
+    pub open_file: Tapered,
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self {
+            piece_values: [
+                Tapered::new(100, 120),
+                Tapered::new(320, 300),
+                Tapered::new(330, 320),
+                Tapered::new(500, 530),
+                Tapered::new(900, 950),
+                Tapered::new(0, 0),
+            ],
+            middlegame_tables: MIDDLEGAME_TABLES.map(SquareTable),
+            endgame_tables: ENDGAME_TABLES.map(SquareTable),
+            mop_up_king_distance: 10,
+
+            passed: [
+                Tapered::new(0, 0),
+                Tapered::new(5, 10),
+                Tapered::new(10, 15),
+                Tapered::new(15, 25),
+                Tapered::new(25, 45),
+                Tapered::new(45, 75),
+                Tapered::new(70, 115),
+                Tapered::new(0, 0),
+            ],
+            connected: [
+                Tapered::new(0, 0),
+                Tapered::new(5, 3),
+                Tapered::new(7, 5),
+                Tapered::new(10, 8),
+                Tapered::new(18, 15),
+                Tapered::new(30, 25),
+                Tapered::new(50, 40),
+                Tapered::new(0, 0),
+            ],
+            doubled: Tapered::new(-10, -25),
+            isolated: Tapered::new(-12, -15),
+            backward: Tapered::new(-8, -12),
+
+            mobility: [
+                Tapered::new(0, 0),
+                Tapered::new(4, 4),
+                Tapered::new(5, 5),
+                Tapered::new(2, 4),
+                Tapered::new(1, 2),
+                Tapered::new(0, 0),
+            ],
+            mobility_base: [0, 4, 6, 7, 13, 0],
+            attack_units: [0, 20, 20, 40, 80, 0],
+            attacker_scale: [0, 0, 50, 75, 88, 94, 97, 99],
+            shield_close: Tapered::new(12, 0),
+            shield_far: Tapered::new(6, 0),
+            semi_open_file: Tapered::new(-10, 0),
+            open_file: Tapered::new(-20, 0),
+        }
+    }
+}
+
+impl EvalParams {
+    /// Every weight in a fixed order, e.g. to adjust them one at a time while tuning
+    pub fn weights_mut(&mut self) -> Vec<&mut i32> {
+        let mut weights = Vec::new();
+
+        fn tapered<'a>(weights: &mut Vec<&'a mut i32>, tapered: &'a mut Tapered) {
+            weights.push(&mut tapered.middlegame);
+            weights.push(&mut tapered.endgame);
+        }
+
+        for value in &mut self.piece_values {
+            tapered(&mut weights, value);
+        }
+        for table in self
+            .middlegame_tables
+            .iter_mut()
+            .chain(&mut self.endgame_tables)
+        {
+            weights.extend(&mut table.0);
+        }
+        weights.push(&mut self.mop_up_king_distance);
+
+        for value in self.passed.iter_mut().chain(&mut self.connected) {
+            tapered(&mut weights, value);
+        }
+        tapered(&mut weights, &mut self.doubled);
+        tapered(&mut weights, &mut self.isolated);
+        tapered(&mut weights, &mut self.backward);
+
+        for value in &mut self.mobility {
+            tapered(&mut weights, value);
+        }
+        weights.extend(&mut self.mobility_base);
+        weights.extend(&mut self.attack_units);
+        weights.extend(&mut self.attacker_scale);
+        tapered(&mut weights, &mut self.shield_close);
+        tapered(&mut weights, &mut self.shield_far);
+        tapered(&mut weights, &mut self.semi_open_file);
+        tapered(&mut weights, &mut self.open_file);
+
+        weights
+    }
+}
+
+/// An error from loading or saving [`EvalParams`]
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ParamsError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// Files need a `.toml` or `.json` extension
+    UnknownFormat(std::path::PathBuf),
+}
+
+#[cfg(feature = "serde")]
+impl core::fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamsError::Io(err) => write!(f, "{err}"),
+            ParamsError::Toml(err) => write!(f, "invalid toml: {err}"),
+            ParamsError::Json(err) => write!(f, "invalid json: {err}"),
+            ParamsError::UnknownFormat(path) => write!(
+                f,
+                "unknown format of {}, expected a .toml or .json file",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for ParamsError {}
+
+#[cfg(feature = "serde")]
+impl EvalParams {
+    pub fn from_toml(s: &str) -> Result<Self, ParamsError> {
+        toml::from_str(s).map_err(ParamsError::Toml)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("all weights can be written as toml")
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, ParamsError> {
+        serde_json::from_str(s).map_err(ParamsError::Json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("all weights can be written as json")
+    }
+
+    /// Load the weights from a toml or json file, depending on its extension
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ParamsError> {
+        let path = path.as_ref();
+        let is_json = is_json(path)?;
+        let s = std::fs::read_to_string(path).map_err(ParamsError::Io)?;
+
+        if is_json {
+            Self::from_json(&s)
+        } else {
+            Self::from_toml(&s)
+        }
+    }
+
+    /// Save the weights to a toml or json file, depending on its extension
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), ParamsError> {
+        let path = path.as_ref();
+        let s = if is_json(path)? {
+            self.to_json()
+        } else {
+            self.to_toml()
+        };
+
+        std::fs::write(path, s).map_err(ParamsError::Io)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_json(path: &std::path::Path) -> Result<bool, ParamsError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => Ok(false),
+        Some("json") => Ok(true),
+        _ => Err(ParamsError::UnknownFormat(path.to_path_buf())),
+    }
+}
+
+// The piece square tables are written from white's point of view, with the 8th rank at the top
+
+#[rustfmt::skip]
+const PAWN_MIDDLEGAME: [i32; 64] = flip([
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+]);
+
+#[rustfmt::skip]
+const PAWN_ENDGAME: [i32; 64] = flip([
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    15, 15, 15, 15, 15, 15, 15, 15,
+     5,  5,  5,  5,  5,  5,  5,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+]);
+
+#[rustfmt::skip]
+const KNIGHT_MIDDLEGAME: [i32; 64] = flip([
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+]);
+
+#[rustfmt::skip]
+const KNIGHT_ENDGAME: [i32; 64] = flip([
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,-10, -5, -5,-10,-20,-40,
+    -30,-10,  5, 10, 10,  5,-10,-30,
+    -30, -5, 10, 15, 15, 10, -5,-30,
+    -30, -5, 10, 15, 15, 10, -5,-30,
+    -30,-10,  5, 10, 10,  5,-10,-30,
+    -40,-20,-10, -5, -5,-10,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+]);
+
+#[rustfmt::skip]
+const BISHOP_MIDDLEGAME: [i32; 64] = flip([
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+]);
+
+#[rustfmt::skip]
+const BISHOP_ENDGAME: [i32; 64] = flip([
+    -15,-10,-10,-10,-10,-10,-10,-15,
+    -10, -5,  0,  0,  0,  0, -5,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -10, -5,  0,  0,  0,  0, -5,-10,
+    -15,-10,-10,-10,-10,-10,-10,-15,
+]);
+
+#[rustfmt::skip]
+const ROOK_MIDDLEGAME: [i32; 64] = flip([
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+]);
+
+#[rustfmt::skip]
+const ROOK_ENDGAME: [i32; 64] = flip([
+     5,  5,  5,  5,  5,  5,  5,  5,
+    10, 10, 10, 10, 10, 10, 10, 10,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+]);
+
+#[rustfmt::skip]
+const QUEEN_MIDDLEGAME: [i32; 64] = flip([
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+]);
+
+#[rustfmt::skip]
+const QUEEN_ENDGAME: [i32; 64] = flip([
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10,  0, 10, 20, 20, 10,  0,-10,
+    -10,  0, 10, 20, 20, 10,  0,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+]);
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME: [i32; 64] = flip([
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+]);
+
+#[rustfmt::skip]
+const KING_ENDGAME: [i32; 64] = flip([
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+]);
+
+const MIDDLEGAME_TABLES: [[i32; 64]; 6] = [
+    PAWN_MIDDLEGAME,
+    KNIGHT_MIDDLEGAME,
+    BISHOP_MIDDLEGAME,
+    ROOK_MIDDLEGAME,
+    QUEEN_MIDDLEGAME,
+    KING_MIDDLEGAME,
+];
+
+const ENDGAME_TABLES: [[i32; 64]; 6] = [
+    PAWN_ENDGAME,
+    KNIGHT_ENDGAME,
+    BISHOP_ENDGAME,
+    ROOK_ENDGAME,
+    QUEEN_ENDGAME,
+    KING_ENDGAME,
+];
+
+/// Tables are written with the 8th rank first, but squares are numbered from a1
+const fn flip<T: Copy>(input: [T; 64]) -> [T; 64] {
+    let mut output = input;
+
+    let mut i = 0;
+
+    while i < 64 {
+        let file = i % 8;
+        let rank = i / 8;
+
+        output[i] = input[(7 - rank) * 8 + file];
+
+        i += 1;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_tables_are_written_from_the_8th_rank() {
+        let params = EvalParams::default();
+        let table = params.endgame_tables[chess_bitboard::Piece::Pawn];
+
+        let rows: [[i32; 8]; 8] = table.into();
+        assert_eq!(rows[1], [80; 8]);
+        assert_eq!(SquareTable::from(rows), table);
+    }
+
+    #[test]
+    fn test_weights_cover_all_params() {
+        let mut params = EvalParams::default();
+        for weight in params.weights_mut() {
+            *weight = 7;
+        }
+
+        assert_eq!(params.open_file, Tapered::new(7, 7));
+        assert_eq!(
+            params.endgame_tables[chess_bitboard::Piece::King][Pos::H8],
+            7
+        );
+        assert_eq!(params.attacker_scale, [7; 8]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_params_round_trip() {
+        let mut params = EvalParams {
+            doubled: Tapered::new(-1, -2),
+            ..EvalParams::default()
+        };
+        params.middlegame_tables[chess_bitboard::Piece::Knight][Pos::F3] = 42;
+
+        assert_eq!(EvalParams::from_toml(&params.to_toml()).unwrap(), params);
+        assert_eq!(EvalParams::from_json(&params.to_json()).unwrap(), params);
+
+        // missing weights keep their defaults
+        let partial = EvalParams::from_toml("mop_up_king_distance = 20").unwrap();
+        assert_eq!(partial.mop_up_king_distance, 20);
+        assert_eq!(partial.passed, EvalParams::default().passed);
+    }
+}
@@ -0,0 +1,216 @@
+use chess_bitboard::{BitBoard, Color, Piece, Pos, Rank};
+use chess_movegen::Board;
+
+use crate::{EvalParams, Tapered};
+
+/// The number of entries in a [`PawnTable`], a power of two so the key can be masked
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+/// The ranks in front of a pawn, indexed by its color and rank
+static FORWARD_RANKS: [[BitBoard; 8]; 2] = {
+    let mut ranks = [[BitBoard::empty(); 8]; 2];
+
+    let mut i = 0;
+
+    while i < 8 {
+        let mut j = 0;
+
+        while j < 8 {
+            let board = BitBoard::from_rank(Rank::const_from_u8(j as u8));
+
+            if j > i {
+                ranks[Color::White as usize][i] = ranks[Color::White as usize][i].or(board);
+            } else if j < i {
+                ranks[Color::Black as usize][i] = ranks[Color::Black as usize][i].or(board);
+            }
+
+            j += 1;
+        }
+
+        i += 1;
+    }
+
+    ranks
+};
+
+/// The pawn structure from white's point of view
+pub(crate) fn evaluate(board: &Board, params: &EvalParams) -> Tapered {
+    let mut score = Tapered::default();
+
+    for color in Color::all() {
+        let own = board[color] & board[Piece::Pawn];
+        let enemy = board[!color] & board[Piece::Pawn];
+
+        let side_score = own
+            .iter()
+            .map(|pos| pawn(params, color, pos, own, enemy))
+            .fold(Tapered::default(), |acc, pawn| acc + pawn);
+
+        score += match color {
+            Color::White => side_score,
+            Color::Black => -side_score,
+        };
+    }
+
+    score
+}
+
+/// The score of a single pawn of `color` on `pos` from its own point of view
+fn pawn(params: &EvalParams, color: Color, pos: Pos, own: BitBoard, enemy: BitBoard) -> Tapered {
+    let file = BitBoard::from_file(pos.file());
+    let rank = BitBoard::from_rank(pos.rank());
+    let adjacent = chess_lookup::ADJACENT_FILES[pos.file()];
+    let ahead = FORWARD_RANKS[color][pos.rank()];
+    let behind = FORWARD_RANKS[!color][pos.rank()];
+
+    let relative_rank = match color {
+        Color::White => pos.rank(),
+        Color::Black => pos.rank().flip(),
+    };
+
+    let mut score = Tapered::default();
+
+    let doubled = (own & file & ahead).any();
+    if doubled {
+        score += params.doubled;
+    }
+
+    // only the front pawn of doubled pawns counts as passed
+    if !doubled && (enemy & (file | adjacent) & ahead).none() {
+        score += params.passed[relative_rank];
+    }
+
+    if (own & adjacent).none() {
+        return score + params.isolated;
+    }
+
+    // the squares a pawn of the other color would attack are the ones pawns of
+    // this color defend `pos` from
+    let supported = (chess_lookup::pawn_attacks_moves(pos, !color) & own).any();
+    let phalanx = (own & adjacent & rank).any();
+
+    if supported || phalanx {
+        score += params.connected[relative_rank];
+    } else if (own & adjacent & behind).none() {
+        let stop = match color {
+            Color::White => pos.shift_up(),
+            Color::Black => pos.shift_down(),
+        };
+
+        if stop.is_some_and(|stop| (chess_lookup::pawn_attacks_moves(stop, color) & enemy).any()) {
+            score += params.backward;
+        }
+    }
+
+    score
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct PawnEntry {
+    key: u64,
+    score: Tapered,
+}
+
+/// A cache of pawn structure scores, indexed by [`Board::pawn_zobrist`].
+///
+/// The pawns change in few moves, so most evaluations find their pawn structure in the table.
+/// Each search thread has its own table, so it isn't shared. It has to be cleared when the
+/// [`EvalParams`] change
+pub(crate) struct PawnTable {
+    entries: Box<[PawnEntry]>,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        // an empty entry has the key of a board without pawns, which correctly scores 0
+        Self {
+            entries: vec![PawnEntry::default(); PAWN_TABLE_SIZE].into_boxed_slice(),
+        }
+    }
+}
+
+impl PawnTable {
+    pub(crate) fn clear(&mut self) {
+        self.entries.fill(PawnEntry::default());
+    }
+
+    /// The pawn structure score of `board` from white's point of view
+    #[inline]
+    pub(crate) fn probe(&mut self, board: &Board, params: &EvalParams) -> Tapered {
+        let key = board.pawn_zobrist();
+        let entry = &mut self.entries[key as usize & (PAWN_TABLE_SIZE - 1)];
+
+        if entry.key != key {
+            *entry = PawnEntry {
+                key,
+                score: evaluate(board, params),
+            };
+        }
+
+        entry.score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pawn_terms() {
+        let params = &EvalParams::default();
+
+        // three isolated pawns which are all passed
+        let board: Board = "4k3/8/8/8/8/8/P1P1P3/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            evaluate(&board, params),
+            (params.passed[1] + params.isolated) * 3
+        );
+
+        // only the front pawn is passed
+        let board: Board = "4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            evaluate(&board, params),
+            params.doubled + params.passed[2] + params.isolated * 2
+        );
+
+        // b2 defends c3, and black's pawns are the same pawns from the other side
+        let board: Board = "4k3/6p1/5p2/8/8/2P5/1P6/4K3 w - - 0 1".parse().unwrap();
+        assert_eq!(evaluate(&board, params), Tapered::default());
+        let white = board[Color::White] & board[Piece::Pawn];
+        let black = board[Color::Black] & board[Piece::Pawn];
+        assert_eq!(
+            pawn(params, Color::White, Pos::B2, white, black),
+            params.passed[1]
+        );
+        assert_eq!(
+            pawn(params, Color::White, Pos::C3, white, black),
+            params.passed[2] + params.connected[2]
+        );
+
+        // d3 can't be defended and c5 stops it from advancing
+        let board: Board = "4k3/8/8/2p5/2P1P3/3P4/8/4K3 w - - 0 1".parse().unwrap();
+        let white = board[Color::White] & board[Piece::Pawn];
+        let black = board[Color::Black] & board[Piece::Pawn];
+        assert_eq!(
+            pawn(params, Color::White, Pos::D3, white, black),
+            params.backward
+        );
+    }
+
+    #[test]
+    fn test_pawn_table_caches_scores() {
+        let params = &EvalParams::default();
+        let mut table = PawnTable::default();
+
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/8/8/8/8/4K2R w - - 0 1",
+        ] {
+            let board: Board = fen.parse().unwrap();
+            assert_eq!(table.probe(&board, params), evaluate(&board, params));
+            assert_eq!(table.probe(&board, params), evaluate(&board, params));
+        }
+
+        assert_eq!(table.probe(&Board::standard(), params), Tapered::default());
+    }
+}
@@ -0,0 +1,177 @@
+use chess_bitboard::{BitBoard, Color, File, Piece, Rank};
+use chess_movegen::Board;
+
+use crate::{EvalParams, Tapered};
+
+/// Piece mobility and king safety from white's point of view
+pub(crate) fn evaluate(board: &Board, params: &EvalParams) -> Tapered {
+    let mut score = Tapered::default();
+
+    for color in Color::all() {
+        let side_score =
+            mobility_and_attacks(board, params, color) + king_shelter(board, params, color);
+
+        score += match color {
+            Color::White => side_score,
+            Color::Black => -side_score,
+        };
+    }
+
+    score
+}
+
+/// The squares attacked by the pawns of `color`
+fn pawn_attacks(board: &Board, color: Color) -> BitBoard {
+    let pawns = board[color] & board[Piece::Pawn];
+    let forward = match color {
+        Color::White => pawns.shift_up(),
+        Color::Black => pawns.shift_down(),
+    };
+
+    forward.shift_left() | forward.shift_right()
+}
+
+/// The mobility of `color`'s pieces, and the attacks they make on the enemy king
+fn mobility_and_attacks(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let occupied = board[Color::White] | board[Color::Black];
+    let enemy_king = board.king_sq(!color);
+    let king_zone = chess_lookup::king_moves(enemy_king).with(enemy_king);
+
+    // squares attacked by enemy pawns aren't safe to move to
+    let safe = !board[color] & !pawn_attacks(board, !color);
+
+    let mut score = Tapered::default();
+    let mut attackers = 0;
+    let mut units = 0;
+
+    for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        for pos in board[color] & board[piece] {
+            let attacks = chess_movegen::piece_attacks(color, piece, pos, occupied);
+
+            let squares = i32::from((attacks & safe).count());
+            score += params.mobility[piece] * (squares - params.mobility_base[piece]);
+
+            let zone_attacks = i32::from((attacks & king_zone).count());
+            if zone_attacks > 0 {
+                attackers += 1;
+                units += zone_attacks * params.attack_units[piece];
+            }
+        }
+    }
+
+    let scale = params.attacker_scale[attackers.min(params.attacker_scale.len() - 1)];
+    let attack = units * scale / 100;
+
+    score + Tapered::new(attack, 0)
+}
+
+/// The pawn shield in front of `color`'s king and the open files around it
+fn king_shelter(board: &Board, params: &EvalParams, color: Color) -> Tapered {
+    let king = board.king_sq(color);
+    let own_pawns = board[color] & board[Piece::Pawn];
+    let pawns = board[Piece::Pawn];
+
+    let files = chess_lookup::ADJACENT_FILES[king.file()] | BitBoard::from_file(king.file());
+
+    let mut score = Tapered::default();
+
+    let ranks = match color {
+        Color::White => [
+            king.rank().shift_up(),
+            king.rank().shift_up().and_then(Rank::shift_up),
+        ],
+        Color::Black => [
+            king.rank().shift_down(),
+            king.rank().shift_down().and_then(Rank::shift_down),
+        ],
+    };
+
+    for (rank, bonus) in ranks
+        .into_iter()
+        .zip([params.shield_close, params.shield_far])
+    {
+        if let Some(rank) = rank {
+            let shield = own_pawns & files & BitBoard::from_rank(rank);
+            score += bonus * i32::from(shield.count());
+        }
+    }
+
+    for file in File::all() {
+        let file = BitBoard::from_file(file);
+
+        if (file & files).none() {
+            continue;
+        }
+
+        if (file & pawns).none() {
+            score += params.open_file;
+        } else if (file & own_pawns).none() {
+            score += params.semi_open_file;
+        }
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirrored_positions() {
+        let params = &EvalParams::default();
+        assert_eq!(evaluate(&Board::standard(), params), Tapered::default());
+
+        let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+            .parse()
+            .unwrap();
+        let mirrored: Board =
+            "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+                .parse()
+                .unwrap();
+
+        assert_eq!(evaluate(&board, params), -evaluate(&mirrored, params));
+    }
+
+    #[test]
+    fn test_mobility() {
+        let params = &EvalParams::default();
+        // a knight in the corner reaches 2 squares, one of them attacked by a pawn
+        let board: Board = "4k3/8/8/8/p7/8/8/N3K3 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            mobility_and_attacks(&board, params, Color::White),
+            params.mobility[Piece::Knight] * (1 - params.mobility_base[Piece::Knight])
+        );
+    }
+
+    #[test]
+    fn test_king_safety() {
+        let params = &EvalParams::default();
+        let sheltered: Board = "4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            king_shelter(&sheltered, params, Color::White),
+            params.shield_close * 3
+        );
+
+        // the same king after pushing the shield pawns, with an open file next to it
+        let exposed: Board = "4k3/8/8/8/8/6PP/8/6K1 w - - 0 1".parse().unwrap();
+        assert_eq!(
+            king_shelter(&exposed, params, Color::White),
+            params.shield_far * 2 + params.open_file
+        );
+
+        // a single piece next to the king isn't an attack yet
+        let board: Board = "6k1/8/8/8/8/8/5PPP/3q2K1 w - - 0 1".parse().unwrap();
+        let single = mobility_and_attacks(&board, params, Color::Black);
+        let board: Board = "6k1/8/8/8/8/7n/5PPP/3q2K1 w - - 0 1".parse().unwrap();
+        let double = mobility_and_attacks(&board, params, Color::Black);
+
+        // the queen and knight both attack two squares around the king
+        let knight = params.mobility[Piece::Knight] * (4 - params.mobility_base[Piece::Knight]);
+        let attack = (2 * params.attack_units[Piece::Queen]
+            + 2 * params.attack_units[Piece::Knight])
+            * params.attacker_scale[2]
+            / 100;
+        assert_eq!(double, single + knight + Tapered::new(attack, 0));
+    }
+}
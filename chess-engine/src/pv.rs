@@ -0,0 +1,81 @@
+use chess_movegen::ChessMove;
+
+/// A triangular table of principal variations, one line for every ply of the search
+#[derive(Default)]
+pub(crate) struct PvTable {
+    lines: Vec<Vec<ChessMove>>,
+}
+
+impl PvTable {
+    #[inline]
+    fn ensure_ply(&mut self, ply: u16) {
+        let len = usize::from(ply) + 2;
+        if self.lines.len() < len {
+            self.lines.resize_with(len, Vec::new);
+        }
+    }
+
+    /// Forget the line at `ply`, called when entering a node at `ply`
+    #[inline]
+    pub fn clear(&mut self, ply: u16) {
+        self.ensure_ply(ply);
+        self.lines[usize::from(ply)].clear();
+    }
+
+    /// `mv` is the new best move at `ply`, so the line at `ply` becomes `mv`
+    /// followed by the line found for the child at `ply + 1`
+    #[inline]
+    pub fn update(&mut self, ply: u16, mv: ChessMove) {
+        self.ensure_ply(ply);
+
+        let (head, tail) = self.lines.split_at_mut(usize::from(ply) + 1);
+        let line = &mut head[usize::from(ply)];
+
+        line.clear();
+        line.push(mv);
+        line.extend_from_slice(&tail[0]);
+    }
+
+    #[inline]
+    pub fn line(&self, ply: u16) -> &[ChessMove] {
+        self.lines
+            .get(usize::from(ply))
+            .map_or(&[], |line| line.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chess_bitboard::Pos;
+
+    use super::*;
+
+    #[test]
+    fn test_update_copies_child_line() {
+        let mv = |source, dest| ChessMove {
+            source,
+            dest,
+            piece: None,
+        };
+
+        let mut pv = PvTable::default();
+        pv.clear(0);
+        pv.clear(1);
+        pv.clear(2);
+        pv.update(2, mv(Pos::E7, Pos::E5));
+        pv.update(1, mv(Pos::E2, Pos::E4));
+
+        // lines are copied up, so searching a sibling at ply 2 keeps the parent lines intact
+        pv.clear(2);
+        pv.update(0, mv(Pos::D2, Pos::D4));
+        assert_eq!(
+            pv.line(0),
+            [
+                mv(Pos::D2, Pos::D4),
+                mv(Pos::E2, Pos::E4),
+                mv(Pos::E7, Pos::E5)
+            ]
+        );
+        assert!(pv.line(2).is_empty());
+    }
+}
@@ -53,6 +53,76 @@ impl Score {
             Score::Max => ScoreKind::Max,
         }
     }
+
+    /// Move a centipawn score by `centipawns` in favour of white, mate scores are left as is
+    #[inline]
+    pub fn offset(self, centipawns: i32) -> Self {
+        match self {
+            Score::Raw(x) => Score::Raw(x.saturating_add(centipawns)),
+            score => score,
+        }
+    }
+
+    /// The smallest score which is better for white than this one
+    #[inline]
+    pub fn next_up(self) -> Self {
+        match self {
+            Score::Min => Score::BlackMateIn(0),
+            Score::BlackMateIn(u16::MAX) => Score::Raw(i32::MIN),
+            Score::BlackMateIn(x) => Score::BlackMateIn(x + 1),
+            Score::Raw(i32::MAX) => Score::WhiteMateIn(u16::MAX),
+            Score::Raw(x) => Score::Raw(x + 1),
+            Score::WhiteMateIn(0) => Score::Max,
+            Score::WhiteMateIn(x) => Score::WhiteMateIn(x - 1),
+            Score::Max => Score::Max,
+        }
+    }
+
+    /// The largest score which is better for black than this one
+    #[inline]
+    pub fn next_down(self) -> Self {
+        match self {
+            Score::Min => Score::Min,
+            Score::BlackMateIn(0) => Score::Min,
+            Score::BlackMateIn(x) => Score::BlackMateIn(x - 1),
+            Score::Raw(i32::MIN) => Score::BlackMateIn(u16::MAX),
+            Score::Raw(x) => Score::Raw(x - 1),
+            Score::WhiteMateIn(u16::MAX) => Score::Raw(i32::MAX),
+            Score::WhiteMateIn(x) => Score::WhiteMateIn(x + 1),
+            Score::Max => Score::WhiteMateIn(0),
+        }
+    }
+
+    /// A search window of `radius` centipawns on either side of this score,
+    /// or the full window around mate scores since those are exact
+    #[inline]
+    pub fn window(self, radius: i32) -> (Self, Self) {
+        match self {
+            Score::Raw(_) => (self.offset(-radius), self.offset(radius)),
+            _ => (Score::Min, Score::Max),
+        }
+    }
+
+    /// Mate scores count plies from the root, convert them to count plies from
+    /// the position at `ply` so they can be reused from any other path
+    #[inline]
+    pub(crate) fn to_tt(self, ply: u16) -> Self {
+        match self {
+            Score::BlackMateIn(x) => Score::BlackMateIn(x.saturating_sub(ply)),
+            Score::WhiteMateIn(x) => Score::WhiteMateIn(x.saturating_sub(ply)),
+            score => score,
+        }
+    }
+
+    /// The inverse of [`Score::to_tt`]
+    #[inline]
+    pub(crate) fn to_root(self, ply: u16) -> Self {
+        match self {
+            Score::BlackMateIn(x) => Score::BlackMateIn(x.saturating_add(ply)),
+            Score::WhiteMateIn(x) => Score::WhiteMateIn(x.saturating_add(ply)),
+            score => score,
+        }
+    }
 }
 
 impl PartialOrd for Score {
@@ -89,3 +159,39 @@ fn test_score_order() {
     assert!(Score::Raw(i32::MIN) > Score::BlackMateIn(3));
     assert!(Score::Raw(-100) < Score::Raw(-3));
 }
+
+#[test]
+fn test_score_window_arithmetic() {
+    let scores = [
+        Score::Min,
+        Score::BlackMateIn(0),
+        Score::BlackMateIn(5),
+        Score::BlackMateIn(u16::MAX),
+        Score::Raw(i32::MIN),
+        Score::Raw(0),
+        Score::Raw(i32::MAX),
+        Score::WhiteMateIn(u16::MAX),
+        Score::WhiteMateIn(5),
+        Score::WhiteMateIn(0),
+        Score::Max,
+    ];
+
+    for score in scores {
+        assert!(score.next_up() >= score);
+        assert!(score.next_down() <= score);
+
+        if score != Score::Max {
+            assert!(score.next_up() > score);
+            assert_eq!(score.next_up().next_down(), score);
+        }
+
+        if score != Score::Min {
+            assert!(score.next_down() < score);
+            assert_eq!(score.next_down().next_up(), score);
+        }
+    }
+
+    assert_eq!(Score::Raw(10).window(25), (Score::Raw(-15), Score::Raw(35)));
+    assert_eq!(Score::WhiteMateIn(3).window(25), (Score::Min, Score::Max));
+    assert_eq!(Score::WhiteMateIn(3).offset(100), Score::WhiteMateIn(3));
+}
@@ -0,0 +1,207 @@
+use std::{cell::Cell, time::Duration};
+
+use chess_bitboard::Color;
+use chess_movegen::ChessMove;
+
+use crate::{Instant, Score, Timeout};
+
+/// The number of moves which are assumed to be left in the game in sudden death
+const DEFAULT_MOVES_TO_GO: u16 = 30;
+
+/// The hard budget is at most this many times the soft budget
+const MAX_SOFT_SCALE: u32 = 4;
+/// Neither budget uses more than this fraction of the remaining time
+const MAX_REMAINING_FRACTION: u32 = 2;
+
+/// Percentage of the soft budget to use depending on for how many iterations in a row the
+/// best move stayed the same
+const STABILITY_SCALE: [u32; 4] = [130, 110, 90, 70];
+
+/// A drop in score of this many centipawns between iterations counts as the position getting
+/// worse, which is worth spending more time on
+const SCORE_DROP: i32 = 30;
+const SCORE_DROP_SCALE: u32 = 50;
+
+/// The time left on the clock of the side to move
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    pub remaining: Duration,
+    /// Added to the clock after every move
+    pub increment: Duration,
+    /// Moves until the next time control, `None` if the rest of the game has to be played in
+    /// the remaining time
+    pub moves_to_go: Option<u16>,
+}
+
+/// A [`Timeout`] which divides the time on the clock between the remaining moves.
+///
+/// The search is stopped between iterations after the soft budget is used up, which is
+/// shortened while the best move stays the same and lengthened when the score drops. The
+/// search is always stopped after the hard budget, even in the middle of an iteration
+///
+/// Pass it to the search by reference, since it tracks the results of the previous iterations
+#[derive(Debug)]
+pub struct TimeManager {
+    start: Instant,
+    turn: Color,
+    soft: Duration,
+    hard: Duration,
+    best_move: Cell<Option<ChessMove>>,
+    stable_iterations: Cell<usize>,
+    score: Cell<Option<Score>>,
+}
+
+impl TimeManager {
+    /// Start timing a move for `turn`, which has `clock` left
+    pub fn new(clock: Clock, turn: Color) -> Self {
+        let moves_to_go = u32::from(clock.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1));
+
+        let max = clock.remaining / MAX_REMAINING_FRACTION;
+
+        let soft = (clock.remaining / moves_to_go + clock.increment * 3 / 4).min(max);
+        let hard = (soft * MAX_SOFT_SCALE).min(max);
+
+        Self {
+            start: Instant::now(),
+            turn,
+            soft,
+            hard,
+            best_move: Cell::new(None),
+            stable_iterations: Cell::new(0),
+            score: Cell::new(None),
+        }
+    }
+
+    /// The time after which no new iteration is started, before adjusting for stability
+    #[inline]
+    pub fn soft_budget(&self) -> Duration {
+        self.soft
+    }
+
+    /// The time after which the search is always stopped
+    #[inline]
+    pub fn hard_budget(&self) -> Duration {
+        self.hard
+    }
+
+    /// How much better `new` is than `old` for the side to move, in centipawns
+    fn gain(&self, old: Score, new: Score) -> Option<i32> {
+        let (Score::Raw(old), Score::Raw(new)) = (old, new) else {
+            return None;
+        };
+
+        Some(match self.turn {
+            Color::White => new - old,
+            Color::Black => old - new,
+        })
+    }
+
+    /// Record the result of an iteration, and return how long the search may take before it
+    /// shouldn't start another iteration
+    fn iteration_budget(&self, best_move: Option<ChessMove>, score: Score) -> Duration {
+        let stable_iterations = if self.best_move.replace(best_move) == best_move {
+            self.stable_iterations.get() + 1
+        } else {
+            0
+        };
+        self.stable_iterations.set(stable_iterations);
+
+        let mut percent = STABILITY_SCALE[stable_iterations.min(STABILITY_SCALE.len() - 1)];
+
+        let old_score = self.score.replace(Some(score));
+        if old_score
+            .and_then(|old| self.gain(old, score))
+            .is_some_and(|gain| gain <= -SCORE_DROP)
+        {
+            percent += SCORE_DROP_SCALE;
+        }
+
+        (self.soft * percent / 100).min(self.hard)
+    }
+}
+
+impl Timeout for TimeManager {
+    #[inline]
+    fn is_complete(&self) -> bool {
+        self.start.elapsed() >= self.hard
+    }
+
+    fn stop_after_iteration(
+        &self,
+        _depth: u16,
+        best_move: Option<ChessMove>,
+        score: Score,
+    ) -> bool {
+        self.start.elapsed() >= self.iteration_budget(best_move, score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chess_bitboard::Pos;
+
+    use super::*;
+
+    #[test]
+    fn test_budgets() {
+        let clock = Clock {
+            remaining: Duration::from_secs(60),
+            increment: Duration::from_secs(1),
+            moves_to_go: None,
+        };
+
+        let time = TimeManager::new(clock, Color::White);
+        assert_eq!(time.soft_budget(), Duration::from_millis(2750));
+        assert_eq!(time.hard_budget(), Duration::from_millis(11000));
+
+        // even the last move before the time control leaves some time in case the search
+        // overshoots
+        let time = TimeManager::new(
+            Clock {
+                moves_to_go: Some(1),
+                ..clock
+            },
+            Color::White,
+        );
+        assert_eq!(time.soft_budget(), Duration::from_secs(30));
+        assert_eq!(time.hard_budget(), Duration::from_secs(30));
+
+        let time = TimeManager::new(
+            Clock {
+                moves_to_go: Some(20),
+                ..clock
+            },
+            Color::White,
+        );
+        assert_eq!(time.soft_budget(), Duration::from_millis(3750));
+        assert_eq!(time.hard_budget(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_stability_and_score_drops() {
+        let mv = |dest| ChessMove {
+            source: Pos::E2,
+            dest,
+            piece: None,
+        };
+
+        let time = TimeManager {
+            soft: Duration::from_secs(1),
+            hard: Duration::from_secs(4),
+            ..TimeManager::new(Clock::default(), Color::Black)
+        };
+        let budget = |dest, score| time.iteration_budget(Some(mv(dest)), score).as_millis();
+
+        // the longer the best move stays the same, the less time is needed
+        assert_eq!(budget(Pos::E4, Score::Raw(0)), 1300);
+        assert_eq!(budget(Pos::E4, Score::Raw(0)), 1100);
+        assert_eq!(budget(Pos::E4, Score::Raw(0)), 900);
+        assert_eq!(budget(Pos::E4, Score::Raw(0)), 700);
+        assert_eq!(budget(Pos::E4, Score::Raw(0)), 700);
+
+        // white gaining is a drop for black
+        assert_eq!(budget(Pos::E4, Score::Raw(SCORE_DROP)), 1200);
+        assert_eq!(budget(Pos::E4, Score::Raw(0)), 700);
+        assert_eq!(budget(Pos::E3, Score::Raw(SCORE_DROP)), 1800);
+    }
+}
@@ -0,0 +1,298 @@
+use core::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use chess_bitboard::{Pos, PromotionPiece};
+use chess_movegen::ChessMove;
+
+use crate::Score;
+
+const DEFAULT_SIZE_MB: usize = 16;
+
+/// Ages are stored in 3 bits, so they wrap around after this many searches
+const AGE_MASK: u8 = 0b111;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is the exact minimax value of the position
+    Exact,
+    /// The true score is at least as large as the stored score (fail high)
+    Lower,
+    /// The true score is at most as large as the stored score (fail low)
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TtEntry {
+    pub depth: u16,
+    pub bound: Bound,
+    pub score: Score,
+    pub best_move: Option<ChessMove>,
+    age: u8,
+}
+
+/// A slot stores the key xor'ed with the data, so a torn write from another thread
+/// makes the key mismatch instead of returning another position's data
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// A fixed size hash table of previously searched positions, indexed by [`Board::zobrist`]
+///
+/// The table is lock-free, so it can be shared between the threads of a parallel search.
+///
+/// [`Board::zobrist`]: chess_movegen::Board::zobrist
+pub struct TranspositionTable {
+    slots: Box<[Slot]>,
+    age: AtomicU8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIZE_MB)
+    }
+}
+
+impl TranspositionTable {
+    /// Create a new transposition table which uses roughly `size_mb` megabytes
+    pub fn new(size_mb: usize) -> Self {
+        let len = (size_mb * 1024 * 1024 / core::mem::size_of::<Slot>()).max(1);
+
+        Self {
+            slots: (0..len).map(|_| Slot::default()).collect(),
+            age: AtomicU8::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+        self.age.store(0, Ordering::Relaxed);
+    }
+
+    /// Mark all current entries as belonging to a previous search, so they may be replaced
+    #[inline]
+    pub fn new_search(&self) {
+        let age = self.age.load(Ordering::Relaxed);
+        self.age
+            .store(age.wrapping_add(1) & AGE_MASK, Ordering::Relaxed);
+    }
+
+    /// Permille of the table which is filled with entries from the current search
+    pub fn hashfull(&self) -> u16 {
+        let age = self.age.load(Ordering::Relaxed);
+        let sample = self.slots.len().min(1000);
+        let used = self.slots[..sample]
+            .iter()
+            .filter_map(|slot| unpack(slot.data.load(Ordering::Relaxed)))
+            .filter(|entry| entry.age == age)
+            .count();
+
+        (used * 1000 / sample) as u16
+    }
+
+    #[inline]
+    fn slot(&self, key: u64) -> &Slot {
+        // maps the key uniformly onto `0..len` without a division
+        let index = ((u128::from(key) * self.slots.len() as u128) >> 64) as usize;
+        &self.slots[index]
+    }
+
+    /// Look up the position with the given zobrist key, `ply` is the distance from the root
+    /// and is used to convert mate scores back to be relative to the root
+    #[inline]
+    pub fn probe(&self, key: u64, ply: u16) -> Option<TtEntry> {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+
+        if slot.key.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+
+        let entry = unpack(data)?;
+
+        Some(TtEntry {
+            score: entry.score.to_root(ply),
+            ..entry
+        })
+    }
+
+    /// Store the result of a search, `ply` is the distance from the root
+    /// and is used to make mate scores relative to the stored position
+    #[inline]
+    pub fn store(
+        &self,
+        key: u64,
+        ply: u16,
+        depth: u16,
+        bound: Bound,
+        score: Score,
+        best_move: Option<ChessMove>,
+    ) {
+        let age = self.age.load(Ordering::Relaxed);
+        let slot = self.slot(key);
+        let old_data = slot.data.load(Ordering::Relaxed);
+        let old_key = slot.key.load(Ordering::Relaxed) ^ old_data;
+        let old = unpack(old_data);
+
+        if let Some(old) = old {
+            // prefer to keep deeper results from the current search around
+            if old.age == age && old.depth > depth {
+                return;
+            }
+        }
+
+        let best_move = match (best_move, old) {
+            (None, Some(old)) if old_key == key => old.best_move,
+            _ => best_move,
+        };
+
+        let data = pack(TtEntry {
+            depth,
+            bound,
+            score: score.to_tt(ply),
+            best_move,
+            age,
+        });
+
+        slot.key.store(key ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+}
+
+// the layout of the data in a slot, from the least significant bit:
+// 16 bits of move, 8 bits of depth, 2 bits of bound, 3 bits of score kind,
+// 3 bits of age and 32 bits of score value. A score kind of 0 marks an empty slot
+const DEPTH_SHIFT: u32 = 16;
+const BOUND_SHIFT: u32 = 24;
+const SCORE_KIND_SHIFT: u32 = 26;
+const AGE_SHIFT: u32 = 29;
+const SCORE_SHIFT: u32 = 32;
+
+const MOVE_PRESENT: u64 = 1 << 15;
+
+fn pack(entry: TtEntry) -> u64 {
+    let best_move = entry.best_move.map_or(0, |mv| {
+        let piece = mv.piece.map_or(0, |piece| piece as u64);
+        u64::from(mv.source.to_u8()) | u64::from(mv.dest.to_u8()) << 6 | piece << 12 | MOVE_PRESENT
+    });
+
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2,
+    };
+
+    let (kind, value): (u64, u32) = match entry.score {
+        Score::Min => (1, 0),
+        Score::BlackMateIn(plies) => (2, u32::from(plies)),
+        Score::Raw(score) => (3, score as u32),
+        Score::WhiteMateIn(plies) => (4, u32::from(plies)),
+        Score::Max => (5, 0),
+    };
+
+    best_move
+        | u64::from(entry.depth.min(u16::from(u8::MAX))) << DEPTH_SHIFT
+        | bound << BOUND_SHIFT
+        | kind << SCORE_KIND_SHIFT
+        | u64::from(entry.age) << AGE_SHIFT
+        | u64::from(value) << SCORE_SHIFT
+}
+
+fn unpack(data: u64) -> Option<TtEntry> {
+    let value = (data >> SCORE_SHIFT) as u32;
+    let score = match (data >> SCORE_KIND_SHIFT) & 0b111 {
+        1 => Score::Min,
+        2 => Score::BlackMateIn(value as u16),
+        3 => Score::Raw(value as i32),
+        4 => Score::WhiteMateIn(value as u16),
+        5 => Score::Max,
+        _ => return None,
+    };
+
+    let bound = match (data >> BOUND_SHIFT) & 0b11 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        _ => Bound::Upper,
+    };
+
+    let best_move = if data & MOVE_PRESENT != 0 {
+        let piece = match (data >> 12) & 0b111 {
+            0 => None,
+            1 => Some(PromotionPiece::Knight),
+            2 => Some(PromotionPiece::Bishop),
+            3 => Some(PromotionPiece::Rook),
+            _ => Some(PromotionPiece::Queen),
+        };
+
+        Some(ChessMove {
+            source: Pos::from_u8((data & 0b111111) as u8)?,
+            dest: Pos::from_u8(((data >> 6) & 0b111111) as u8)?,
+            piece,
+        })
+    } else {
+        None
+    };
+
+    Some(TtEntry {
+        depth: ((data >> DEPTH_SHIFT) & 0xff) as u16,
+        bound,
+        score,
+        best_move,
+        age: ((data >> AGE_SHIFT) as u8) & AGE_MASK,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mate_scores_are_adjusted() {
+        let tt = TranspositionTable::new(1);
+
+        // found a mate 7 plies from the root while at ply 3
+        tt.store(42, 3, 2, Bound::Exact, Score::WhiteMateIn(7), None);
+
+        // the same position reached at ply 5 is mate 9 plies from the root
+        let entry = tt.probe(42, 5).unwrap();
+        assert_eq!(entry.score, Score::WhiteMateIn(9));
+
+        tt.store(43, 4, 2, Bound::Upper, Score::BlackMateIn(6), None);
+        let entry = tt.probe(43, 2).unwrap();
+        assert_eq!(entry.score, Score::BlackMateIn(4));
+        assert_eq!(entry.bound, Bound::Upper);
+
+        assert!(tt.probe(44, 0).is_none());
+    }
+
+    #[test]
+    fn test_entries_round_trip() {
+        let tt = TranspositionTable::new(1);
+        let mv = ChessMove {
+            source: Pos::E7,
+            dest: Pos::E8,
+            piece: Some(PromotionPiece::Knight),
+        };
+
+        tt.store(7, 0, 12, Bound::Lower, Score::Raw(-345), Some(mv));
+        let entry = tt.probe(7, 0).unwrap();
+        assert_eq!(entry.depth, 12);
+        assert_eq!(entry.bound, Bound::Lower);
+        assert_eq!(entry.score, Score::Raw(-345));
+        assert_eq!(entry.best_move, Some(mv));
+
+        // storing without a move keeps the previous best move of the same position
+        tt.store(7, 0, 13, Bound::Exact, Score::Max, None);
+        let entry = tt.probe(7, 0).unwrap();
+        assert_eq!(entry.score, Score::Max);
+        assert_eq!(entry.best_move, Some(mv));
+    }
+}
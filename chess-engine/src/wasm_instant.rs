@@ -20,6 +20,10 @@ impl Instant {
 
         Self { time }
     }
+
+    pub fn elapsed(&self) -> Duration {
+        Self::now().time.saturating_sub(self.time)
+    }
 }
 
 impl std::ops::Add<Duration> for Instant {
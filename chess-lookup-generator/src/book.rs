@@ -2,12 +2,15 @@ use std::{
     collections::{hash_map::Entry, HashMap},
     error::Error,
     io::{BufReader, Read},
+    ops::ControlFlow,
 };
 
 use bstr::ByteSlice;
-use chess_bitboard::{File, Pos, Rank};
-use chess_movegen::{Board, ChessMove};
-use pgn_reader::BufferedReader;
+use chess_movegen::{
+    pgn::{self, PgnReader},
+    san::ParseSanError,
+    Board, ChessMove,
+};
 
 #[derive(Debug, serde::Serialize)]
 struct MoveTrie {
@@ -70,7 +73,7 @@ fn serialize_chess_move<S: serde::Serializer>(
     map.end()
 }
 
-#[allow(clippy::type_complexity)]
+#[allow(clippy::type_complexity, unreachable_code)]
 pub fn read_lichess_games() -> Result<Vec<u16>, Box<dyn Error>> {
     let s = std::fs::read("temp/moves_trie.json")?;
     let start = std::time::Instant::now();
@@ -118,10 +121,10 @@ pub fn read_lichess_games() -> Result<Vec<u16>, Box<dyn Error>> {
                 loop {
                     let x: Vec<u8> = match rx.recv() {
                         Ok(x) => x,
-                        Err(_) => break Ok::<_, std::io::Error>(trie),
+                        Err(_) => break Ok::<_, pgn::PgnError>(trie),
                     };
 
-                    let mut reader = BufferedReader::new(x.as_slice());
+                    let mut reader = PgnReader::new(x.as_slice());
                     let mut visitor = Visitor {
                         both_high_elo: 0,
                         games: 0,
@@ -327,10 +330,10 @@ pub struct Visitor<'a> {
     max_counts: Vec<u32>,
 }
 
-impl pgn_reader::Visitor for Visitor<'_> {
-    type Result = ();
+impl pgn::Visitor for Visitor<'_> {
+    type Output = ();
 
-    fn end_game(&mut self) -> Self::Result {
+    fn end_game(&mut self) -> Self::Output {
         if self.moves.len() != 8 {
             return;
         }
@@ -367,120 +370,39 @@ impl pgn_reader::Visitor for Visitor<'_> {
         self.board = Board::standard();
         self.moves.clear();
         self.games += 1;
-    }
-
-    fn begin_headers(&mut self) {
         self.both_high_elo = 0;
     }
 
-    fn header(&mut self, key: &[u8], value: pgn_reader::RawHeader<'_>) {
-        if key == b"WhiteElo" || key == b"BlackElo" {
-            let elo: u32 = value.decode_utf8().unwrap().parse().unwrap();
+    fn tag(&mut self, name: &str, value: &str) {
+        if name == "WhiteElo" || name == "BlackElo" {
+            let elo: u32 = value.parse().unwrap();
 
             self.both_high_elo += u8::from(elo < 1800);
         }
     }
 
-    fn end_headers(&mut self) -> pgn_reader::Skip {
-        pgn_reader::Skip(self.both_high_elo != 2)
+    fn end_tags(&mut self, _start: &Board) -> ControlFlow<()> {
+        if self.both_high_elo == 2 {
+            ControlFlow::Continue(())
+        } else {
+            ControlFlow::Break(())
+        }
     }
 
-    fn san(&mut self, san_plus: pgn_reader::SanPlus) {
+    fn san(&mut self, san: &str) -> Result<(), ParseSanError> {
         if self.moves.len() >= 8 || self.both_high_elo != 2 {
-            return;
+            return Ok(());
         }
 
-        let san = san_plus.san;
-
-        let mv = match san {
-            pgn_reader::San::Normal {
-                role,
-                file,
-                rank,
-                capture: _,
-                to,
-                promotion,
-            } => {
-                let promotion = match promotion {
-                    Some(_) => {
-                        self.both_high_elo = 0;
-                        return;
-                    }
-                    None => None,
-                };
-
-                let dest =
-                    chess_bitboard::Pos::new(convert_file(to.file()), convert_rank(to.rank()));
-                let file = file.map(convert_file);
-                let rank = rank.map(convert_rank);
-
-                let mut rule_c = 0;
-                let piece = role
-                    .upper_char()
-                    .encode_utf8(core::slice::from_mut(&mut rule_c))
-                    .parse::<chess_bitboard::Piece>()
-                    .unwrap();
-
-                let mut moves = self.board.legals();
-                // moves.set_mask(dest.into());
-
-                let mut only_legal = None;
-                for mv in &mut moves {
-                    if self.board.raw().piece_of(mv.source) != Some(piece) {
-                        continue;
-                    }
-
-                    if mv.dest != dest {
-                        continue;
-                    }
-
-                    if let Some(file) = file {
-                        if file != mv.source.file() {
-                            continue;
-                        }
-                    }
-
-                    if let Some(rank) = rank {
-                        if rank != mv.source.rank() {
-                            continue;
-                        }
-                    }
-
-                    if promotion != mv.piece {
-                        continue;
-                    }
-
-                    assert!(only_legal.is_none());
+        let mv = self.board.parse_san(san)?;
 
-                    only_legal = Some(mv);
-                }
-
-                only_legal.unwrap()
-            }
-            pgn_reader::San::Castle(castle) => {
-                pub const BACKRANK: [Rank; 2] = [Rank::_1, Rank::_8];
-
-                let king_to_file = convert_file(castle.king_to_file());
-                let backrank = BACKRANK[self.board.turn()];
-                let king_file = File::E;
-
-                ChessMove {
-                    source: Pos::new(king_file, backrank),
-                    dest: Pos::new(king_to_file, backrank),
-                    piece: None,
-                }
-            }
-            pgn_reader::San::Put { role: _, to: _ } | pgn_reader::San::Null => unreachable!(),
-        };
+        if mv.piece.is_some() {
+            self.both_high_elo = 0;
+            return Ok(());
+        }
 
         assert!(self.board.move_mut(mv));
         self.moves.push(mv);
+        Ok(())
     }
 }
-
-fn convert_file(file: pgn_reader::File) -> chess_bitboard::File {
-    chess_bitboard::File::all().nth(file as usize).unwrap()
-}
-fn convert_rank(rank: pgn_reader::Rank) -> chess_bitboard::Rank {
-    chess_bitboard::Rank::all().nth(rank as usize).unwrap()
-}
@@ -1,7 +1,10 @@
 use std::{collections::HashMap, error::Error};
 
-use chess_bitboard::{File, Pos, Rank};
-use chess_movegen::{Board, ChessMove};
+use chess_movegen::{
+    pgn::{self, PgnReader},
+    san::ParseSanError,
+    Board, ChessMove,
+};
 
 const VOLA: &str = include_str!("eco/vola.txt");
 const VOLB: &str = include_str!("eco/volb.txt");
@@ -16,7 +19,6 @@ struct MoveTrieMap {
 }
 
 #[allow(clippy::type_complexity)]
-
 pub fn read_eco() -> Result<(Vec<u16>, Vec<&'static str>), Box<dyn Error>> {
     let volumes = [VOLA, VOLB, VOLC, VOLD, VOLE];
 
@@ -46,9 +48,10 @@ pub fn read_eco() -> Result<(Vec<u16>, Vec<&'static str>), Box<dyn Error>> {
 
         let colon = opening.find(':').unwrap();
         let (name, opening) = opening.split_at(colon);
-        let opening = &opening[1..];
+        // some openings end with notes on alternatives, like `3. g3, 3. c4` or `(3...Nf6--C47)`
+        let opening = opening[1..].split([',', '(']).next().unwrap();
 
-        let mut reader = pgn_reader::BufferedReader::new(opening.as_bytes());
+        let mut reader = PgnReader::new(opening.as_bytes());
 
         let moves = reader
             .read_game(&mut Visitor {
@@ -124,106 +127,18 @@ pub struct Visitor {
     moves: Vec<ChessMove>,
 }
 
-impl pgn_reader::Visitor for Visitor {
-    type Result = Vec<ChessMove>;
+impl pgn::Visitor for Visitor {
+    type Output = Vec<ChessMove>;
 
-    fn end_game(&mut self) -> Self::Result {
+    fn end_game(&mut self) -> Self::Output {
         core::mem::take(&mut self.moves)
     }
 
-    fn san(&mut self, san_plus: pgn_reader::SanPlus) {
-        let san = san_plus.san;
-
-        let mv = match san {
-            pgn_reader::San::Normal {
-                role,
-                file,
-                rank,
-                capture: _,
-                to,
-                promotion,
-            } => {
-                let promotion = match promotion {
-                    Some(pgn_reader::Role::Knight) => todo!(),
-                    Some(pgn_reader::Role::Bishop) => todo!(),
-                    Some(pgn_reader::Role::Rook) => todo!(),
-                    Some(pgn_reader::Role::Queen) => todo!(),
-                    Some(_) => unreachable!(),
-                    None => None,
-                };
-
-                let dest =
-                    chess_bitboard::Pos::new(convert_file(to.file()), convert_rank(to.rank()));
-                let file = file.map(convert_file);
-                let rank = rank.map(convert_rank);
-
-                let mut rule_c = 0;
-                let piece = role
-                    .upper_char()
-                    .encode_utf8(core::slice::from_mut(&mut rule_c))
-                    .parse::<chess_bitboard::Piece>()
-                    .unwrap();
-
-                let mut moves = self.board.legals();
-                // moves.set_mask(dest.into());
-
-                let mut only_legal = None;
-                for mv in &mut moves {
-                    if self.board.raw().piece_of(mv.source) != Some(piece) {
-                        continue;
-                    }
-
-                    if mv.dest != dest {
-                        continue;
-                    }
-
-                    if let Some(file) = file {
-                        if file != mv.source.file() {
-                            continue;
-                        }
-                    }
-
-                    if let Some(rank) = rank {
-                        if rank != mv.source.rank() {
-                            continue;
-                        }
-                    }
-
-                    if promotion != mv.piece {
-                        continue;
-                    }
-
-                    assert!(only_legal.is_none());
-
-                    only_legal = Some(mv);
-                }
-
-                only_legal.unwrap()
-            }
-            pgn_reader::San::Castle(castle) => {
-                pub const BACKRANK: [Rank; 2] = [Rank::_1, Rank::_8];
-
-                let king_to_file = convert_file(castle.king_to_file());
-                let backrank = BACKRANK[self.board.turn()];
-                let king_file = File::E;
-
-                ChessMove {
-                    source: Pos::new(king_file, backrank),
-                    dest: Pos::new(king_to_file, backrank),
-                    piece: None,
-                }
-            }
-            pgn_reader::San::Put { role: _, to: _ } | pgn_reader::San::Null => unreachable!(),
-        };
+    fn san(&mut self, san: &str) -> Result<(), ParseSanError> {
+        let mv = self.board.parse_san(san)?;
 
         assert!(self.board.move_mut(mv));
         self.moves.push(mv);
+        Ok(())
     }
 }
-
-fn convert_file(file: pgn_reader::File) -> chess_bitboard::File {
-    chess_bitboard::File::all().nth(file as usize).unwrap()
-}
-fn convert_rank(rank: pgn_reader::Rank) -> chess_bitboard::Rank {
-    chess_bitboard::Rank::all().nth(rank as usize).unwrap()
-}
@@ -151,45 +151,11 @@ pub const BACKRANK: [Rank; 2] = [Rank::_1, Rank::_8];
 pub const BACKRANK_BB: [BitBoard; 2] =
     [BitBoard::from_rank(Rank::_1), BitBoard::from_rank(Rank::_8)];
 
-pub const CASTLE_MOVES: BitBoard = BitBoard::empty()
-    .with(Pos::C1)
-    .with(Pos::C8)
-    .with(Pos::E1)
-    .with(Pos::E8)
-    .with(Pos::G1)
-    .with(Pos::G8);
-
 pub const PAWN_DOUBLE_MOVE: [BitBoard; 2] = [
     BitBoard::from_rank(Rank::_2).or(BitBoard::from_rank(Rank::_4)),
     BitBoard::from_rank(Rank::_5).or(BitBoard::from_rank(Rank::_7)),
 ];
 
-pub const ROOK_CASTLE_QUEENSIDE: BitBoard =
-    BitBoard::from_file(File::A).or(BitBoard::from_file(File::D));
-pub const ROOK_CASTLE_KINGSIDE: BitBoard =
-    BitBoard::from_file(File::H).or(BitBoard::from_file(File::F));
-
-pub const CASTLE_ROOK_START: [File; 8] = [
-    File::A,
-    File::A,
-    File::A,
-    File::A,
-    File::H,
-    File::H,
-    File::H,
-    File::H,
-];
-pub const CASTLE_ROOK_END: [File; 8] = [
-    File::D,
-    File::D,
-    File::D,
-    File::D,
-    File::F,
-    File::F,
-    File::F,
-    File::F,
-];
-
 pub const PROMOTION_RANK: [Rank; 2] = [Rank::_8, Rank::_1];
 
 pub const PAWN_DOUBLE_MOVE_SOURCE_RANK: [Rank; 2] = [Rank::_2, Rank::_7];
@@ -223,17 +189,6 @@ pub static ADJACENT_RANKS: [BitBoard; 8] = {
     ranks
 };
 
-pub const KINGSIDE_CASTLE_FILES: BitBoard =
-    BitBoard::from_file(File::F).or(BitBoard::from_file(File::G));
-pub const QUEENSIDE_CASTLE_FILES: BitBoard = BitBoard::from_file(File::B)
-    .or(BitBoard::from_file(File::C))
-    .or(BitBoard::from_file(File::D));
-
-pub const KINGSIDE_CASTLE_SAFE_FILES: BitBoard =
-    BitBoard::from_file(File::F).or(BitBoard::from_file(File::G));
-pub const QUEENSIDE_CASTLE_SAFE_FILES: BitBoard =
-    BitBoard::from_file(File::C).or(BitBoard::from_file(File::D));
-
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct BookMoves {
     index: usize,
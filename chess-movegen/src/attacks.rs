@@ -0,0 +1,112 @@
+use chess_bitboard::{BitBoard, Color, Piece, Pos};
+
+use crate::{raw::RawBoard, Board};
+
+/// The squares a `piece` of `color` on `pos` attacks, with the pieces in `occupied`
+/// blocking sliders. Pawns only attack diagonally
+#[inline]
+pub fn piece_attacks(color: Color, piece: Piece, pos: Pos, occupied: BitBoard) -> BitBoard {
+    match piece {
+        Piece::Pawn => chess_lookup::pawn_attacks_moves(pos, color),
+        Piece::Knight => chess_lookup::knight_moves(pos),
+        Piece::Bishop => chess_lookup::bishop_moves(pos, occupied),
+        Piece::Rook => chess_lookup::rook_moves(pos, occupied),
+        Piece::Queen => {
+            chess_lookup::bishop_moves(pos, occupied) | chess_lookup::rook_moves(pos, occupied)
+        }
+        Piece::King => chess_lookup::king_moves(pos),
+    }
+}
+
+impl RawBoard {
+    /// The pieces of both colors attacking `pos`, with the pieces in `occupied` blocking
+    /// sliders. Pieces which aren't in `occupied` still attack, so remove them from the
+    /// result if they have been captured
+    #[inline]
+    pub fn attackers_to(&self, pos: Pos, occupied: BitBoard) -> BitBoard {
+        let bishops = self[Piece::Bishop] | self[Piece::Queen];
+        let rooks = self[Piece::Rook] | self[Piece::Queen];
+        let pawns = self[Piece::Pawn];
+
+        (chess_lookup::pawn_attacks_moves(pos, Color::Black) & pawns & self[Color::White])
+            | (chess_lookup::pawn_attacks_moves(pos, Color::White) & pawns & self[Color::Black])
+            | (chess_lookup::knight_moves(pos) & self[Piece::Knight])
+            | (chess_lookup::king_moves(pos) & self[Piece::King])
+            | (chess_lookup::bishop_moves(pos, occupied) & bishops)
+            | (chess_lookup::rook_moves(pos, occupied) & rooks)
+    }
+
+    /// Whether any piece of `color` attacks `pos`, with the pieces in `occupied` blocking sliders
+    #[inline]
+    pub fn is_attacked(&self, pos: Pos, color: Color, occupied: BitBoard) -> bool {
+        (self.attackers_to(pos, occupied) & self[color]).any()
+    }
+
+    /// Every square attacked by a piece of `color`, with the pieces in `occupied` blocking sliders
+    pub fn attacked_squares(&self, color: Color, occupied: BitBoard) -> BitBoard {
+        let mut attacked = BitBoard::empty();
+
+        for piece in Piece::all() {
+            for pos in self[color] & self[piece] {
+                attacked |= piece_attacks(color, piece, pos, occupied);
+            }
+        }
+
+        attacked
+    }
+
+    /// The sliders of both colors which attack `pos` through exactly one other piece, e.g. a
+    /// rook behind a piece it would pin
+    pub fn xray_attackers(&self, pos: Pos, occupied: BitBoard) -> BitBoard {
+        let bishops = self[Piece::Bishop] | self[Piece::Queen];
+        let rooks = self[Piece::Rook] | self[Piece::Queen];
+
+        let bishop_attacks = chess_lookup::bishop_moves(pos, occupied);
+        let rook_attacks = chess_lookup::rook_moves(pos, occupied);
+
+        // remove the pieces directly in front of `pos` to find the ones behind them
+        let xray_bishops = chess_lookup::bishop_moves(pos, occupied & !bishop_attacks);
+        let xray_rooks = chess_lookup::rook_moves(pos, occupied & !rook_attacks);
+
+        ((xray_bishops & !bishop_attacks & bishops) | (xray_rooks & !rook_attacks & rooks))
+            & occupied
+    }
+}
+
+impl Board {
+    /// The pieces of `color` attacking `pos`
+    #[inline]
+    pub fn attackers_to(&self, pos: Pos, color: Color) -> BitBoard {
+        self.raw.attackers_to(pos, self.raw.all()) & self.raw[color]
+    }
+
+    /// Whether any piece of `color` attacks `pos`
+    #[inline]
+    pub fn is_attacked(&self, pos: Pos, color: Color) -> bool {
+        self.attackers_to(pos, color).any()
+    }
+
+    /// Every square attacked by a piece of `color`
+    #[inline]
+    pub fn attacked_squares(&self, color: Color) -> BitBoard {
+        self.raw.attacked_squares(color, self.raw.all())
+    }
+
+    /// The sliders of `color` which attack `pos` through exactly one other piece
+    #[inline]
+    pub fn xray_attackers(&self, pos: Pos, color: Color) -> BitBoard {
+        self.raw.xray_attackers(pos, self.raw.all()) & self.raw[color]
+    }
+
+    /// The pieces giving check to the side to move
+    #[inline]
+    pub fn checkers(&self) -> BitBoard {
+        self.checkers
+    }
+
+    /// The pieces of the side to move which are pinned to its king
+    #[inline]
+    pub fn pinned(&self) -> BitBoard {
+        self.pinned & self.raw[self.turn]
+    }
+}
@@ -1,89 +1,46 @@
 use std::fmt::Write;
 
-use chess_bitboard::{Color, Pos, Side};
-
-const __: CastleRights = CastleRights::empty().not();
-const WK: CastleRights = CastleRights::empty().with(Side::King, Color::White).not();
-const WQ: CastleRights = CastleRights::empty().with(Side::Queen, Color::White).not();
-const WB: CastleRights = CastleRights::empty()
-    .with(Side::Queen, Color::White)
-    .with(Side::King, Color::White)
-    .not();
-
-const BK: CastleRights = CastleRights::empty().with(Side::King, Color::Black).not();
-const BQ: CastleRights = CastleRights::empty().with(Side::Queen, Color::Black).not();
-const BB: CastleRights = CastleRights::empty()
-    .with(Side::Queen, Color::Black)
-    .with(Side::King, Color::Black)
-    .not();
-
-#[rustfmt::skip]
-const CASTLE_RIGHTS_PER_SQ_INIT: [[CastleRights; 64]; 2] = [
-    [
-    WQ, __, __, __, WB, __, __, WK, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-],
-[
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    __, __, __, __, __, __, __, __, 
-    BQ, __, __, __, BB, __, __, BK, 
-]];
-
-static CASTLE_RIGHTS_PER_SQ: [[CastleRights; 64]; 2] = CASTLE_RIGHTS_PER_SQ_INIT;
-
-const _: () = {
-    assert!(CASTLE_RIGHTS_PER_SQ_INIT[Color::White as usize][Pos::A1 as usize].0 == WQ.0);
-    assert!(CASTLE_RIGHTS_PER_SQ_INIT[Color::White as usize][Pos::H1 as usize].0 == WK.0);
-    assert!(CASTLE_RIGHTS_PER_SQ_INIT[Color::White as usize][Pos::E1 as usize].0 == WB.0);
-
-    assert!(CASTLE_RIGHTS_PER_SQ_INIT[Color::Black as usize][Pos::A8 as usize].0 == BQ.0);
-    assert!(CASTLE_RIGHTS_PER_SQ_INIT[Color::Black as usize][Pos::H8 as usize].0 == BK.0);
-    assert!(CASTLE_RIGHTS_PER_SQ_INIT[Color::Black as usize][Pos::E8 as usize].0 == BB.0);
-
-    assert!(__.0 != WQ.0);
-    assert!(__.0 != WK.0);
-    assert!(__.0 != WB.0);
-
-    assert!(__.0 != BQ.0);
-    assert!(__.0 != BK.0);
-    assert!(__.0 != BB.0);
-};
-
-#[repr(transparent)]
+use chess_bitboard::{Color, File, Piece, Pos, Side};
+
+use crate::{Board, ChessMove};
+
+/// The files of the rooks in the standard starting position, indexed by side
+const STANDARD_ROOKS: [File; 2] = [File::H, File::A];
+
+/// The castle rights in FEN, indexed by color and side
+static CASTLE_RIGHTS: [[char; 2]; 2] = [['K', 'Q'], ['k', 'q']];
+
+/// The sides each color may still castle towards, and the files of the rooks they castle with.
+///
+/// The king castles from the square it's on, which can't change while it may castle, so in
+/// Chess960 the king and its rooks can start on any file
+#[repr(C)]
 #[cfg_attr(feature = "abi_stable", derive(abi_stable::StableAbi))]
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub struct CastleRights(u8);
+pub struct CastleRights {
+    rights: u8,
+    /// Indexed by color and side, the sides which can't castle keep the standard files
+    rooks: [[File; 2]; 2],
+}
 
 impl core::fmt::Debug for CastleRights {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        static CASTLE_RIGHTS: [[char; 2]; 2] = [['K', 'Q'], ['k', 'q']];
+        for color in Color::all() {
+            for side in Side::all() {
+                if !self.contains(side, color) {
+                    continue;
+                }
 
-        let rights = Color::all().flat_map(|color| {
-            Side::all().filter_map(move |side| {
-                if self.contains(side, color) {
-                    Some(CASTLE_RIGHTS[color][side])
+                let file = self.rook_file(side, color);
+                if file == STANDARD_ROOKS[side] {
+                    f.write_char(CASTLE_RIGHTS[color][side])?
                 } else {
-                    None
+                    f.write_char(file_char(file, color))?
                 }
-            })
-        });
-
-        for right in rights {
-            f.write_char(right)?
+            }
         }
 
-        if self.0 == 0 {
+        if self.rights == 0 {
             f.write_str("-")?
         }
 
@@ -91,6 +48,15 @@ impl core::fmt::Debug for CastleRights {
     }
 }
 
+/// The file as written in Shredder-FEN, uppercase for white
+fn file_char(file: File, color: Color) -> char {
+    let file = (b'A' + file as u8) as char;
+    match color {
+        Color::White => file,
+        Color::Black => file.to_ascii_lowercase(),
+    }
+}
+
 const fn offset(side: Side, color: Color) -> u32 {
     side as u32 + color as u32 * 2
 }
@@ -98,7 +64,10 @@ const fn offset(side: Side, color: Color) -> u32 {
 impl CastleRights {
     #[inline]
     pub const fn empty() -> Self {
-        Self(0)
+        Self {
+            rights: 0,
+            rooks: [STANDARD_ROOKS; 2],
+        }
     }
 
     #[inline]
@@ -111,18 +80,24 @@ impl CastleRights {
     }
 
     #[inline]
-    pub const fn not(self) -> Self {
-        Self(!self.0)
+    pub const fn without(mut self, side: Side, color: Color) -> Self {
+        self.rights &= !(1 << offset(side, color));
+        self.rooks[color as usize][side as usize] = STANDARD_ROOKS[side as usize];
+        self
     }
 
+    /// Castling with the rook on the standard file
     #[inline]
-    pub const fn without(self, side: Side, color: Color) -> Self {
-        Self(self.0 & !(1 << offset(side, color)))
+    pub const fn with(self, side: Side, color: Color) -> Self {
+        self.with_rook(side, color, STANDARD_ROOKS[side as usize])
     }
 
+    /// Castling with the rook on `file`
     #[inline]
-    pub const fn with(self, side: Side, color: Color) -> Self {
-        Self(self.0 | 1 << offset(side, color))
+    pub const fn with_rook(mut self, side: Side, color: Color, file: File) -> Self {
+        self.rights |= 1 << offset(side, color);
+        self.rooks[color as usize][side as usize] = file;
+        self
     }
 
     #[inline]
@@ -135,27 +110,156 @@ impl CastleRights {
         *self = self.with(side, color)
     }
 
+    #[inline]
+    pub fn add_rook(&mut self, side: Side, color: Color, file: File) {
+        *self = self.with_rook(side, color, file)
+    }
+
     #[inline]
     pub const fn contains(self, side: Side, color: Color) -> bool {
-        self.0 & (1 << offset(side, color)) != 0
+        self.rights & (1 << offset(side, color)) != 0
+    }
+
+    /// The file of the rook `color` castles with towards `side`
+    #[inline]
+    pub const fn rook_file(self, side: Side, color: Color) -> File {
+        self.rooks[color as usize][side as usize]
     }
 
     #[inline]
     pub const fn to_index(self) -> usize {
-        let index = self.0 as usize;
+        let index = self.rights as usize;
         if index >= 16 {
             unsafe { core::hint::unreachable_unchecked() }
         }
         index
     }
 
+    /// Remove the rights of `color` to castle with a rook on `pos`
     #[inline]
-    pub const fn contains_color(self, color: Color) -> bool {
-        self.0 & ((1 << offset(Side::King, color)) | (1 << offset(Side::Queen, color))) != 0
+    pub(crate) fn remove_for_sq(&mut self, color: Color, pos: Pos) {
+        if pos.rank() != chess_lookup::BACKRANK[color] {
+            return;
+        }
+
+        for side in Side::all() {
+            if self.contains(side, color) && self.rook_file(side, color) == pos.file() {
+                self.remove(side, color);
+            }
+        }
     }
 
-    pub(crate) fn remove_for_sq(&mut self, turn: Color, end: Pos) {
-        let rights = CASTLE_RIGHTS_PER_SQ[turn][end].0;
-        self.0 &= rights;
+    #[inline]
+    pub(crate) fn remove_color(&mut self, color: Color) {
+        self.remove(Side::King, color);
+        self.remove(Side::Queen, color);
+    }
+}
+
+impl Board {
+    /// The square the king moves to when castling towards `side`.
+    ///
+    /// If the king starts on the e-file, castling is written as the king moving two squares.
+    /// Otherwise the king may move a single square or not at all, so castling is written as the
+    /// king moving onto its own rook, as in Chess960
+    #[inline]
+    pub(crate) fn castle_move_dest(&self, color: Color, side: Side) -> Pos {
+        let king = self.king_sq(color);
+        if king.file() == File::E {
+            Pos::new(castle_king_file(side), king.rank())
+        } else {
+            Pos::new(self.castle_rights.rook_file(side, color), king.rank())
+        }
+    }
+
+    /// Which side `mv` castles towards, if it does. `mv` must be a legal move
+    #[inline]
+    pub(crate) fn castle_side(&self, mv: ChessMove) -> Option<Side> {
+        if self.raw.piece_of(mv.source) != Some(Piece::King) {
+            return None;
+        }
+
+        let onto_rook = self.raw[self.turn].contains(mv.dest);
+        let two_squares =
+            mv.source.file() == File::E && matches!(mv.dest.file(), File::C | File::G);
+
+        if !(onto_rook || two_squares) {
+            return None;
+        }
+
+        Some(if mv.dest.file() > mv.source.file() {
+            Side::King
+        } else {
+            Side::Queen
+        })
+    }
+
+    /// The squares the king and the rook of `color` end up on after castling towards `side`
+    #[inline]
+    pub(crate) fn castle_targets(color: Color, side: Side) -> (Pos, Pos) {
+        let rank = chess_lookup::BACKRANK[color];
+        let rook = match side {
+            Side::King => File::F,
+            Side::Queen => File::D,
+        };
+
+        (Pos::new(castle_king_file(side), rank), Pos::new(rook, rank))
+    }
+
+    /// Writes the castle rights of a FEN. X-FEN writes `KQkq` unless another rook is further out
+    /// on that side, and the file of the rook otherwise, while Shredder-FEN always writes the file
+    pub(crate) fn write_castle_rights(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        shredder: bool,
+    ) -> std::fmt::Result {
+        let cr = self.castle_rights;
+        for color in Color::all() {
+            let rooks = self.raw[color] & self.raw[Piece::Rook] & chess_lookup::BACKRANK_BB[color];
+
+            for side in Side::all() {
+                if !cr.contains(side, color) {
+                    continue;
+                }
+
+                let file = cr.rook_file(side, color);
+                let outermost = rooks.iter().all(|rook| match side {
+                    Side::King => rook.file() <= file,
+                    Side::Queen => rook.file() >= file,
+                });
+
+                if outermost && !shredder {
+                    f.write_char(CASTLE_RIGHTS[color][side])?
+                } else {
+                    f.write_char(file_char(file, color))?
+                }
+            }
+        }
+
+        if cr.rights == 0 {
+            f.write_str("-")?
+        }
+
+        Ok(())
+    }
+
+    /// The squares the rook of the side to move moves from and to when castling towards `side`
+    #[inline]
+    pub(crate) fn castle_rook_move(&self, side: Side) -> (Pos, Pos) {
+        let source = Pos::new(
+            self.castle_rights.rook_file(side, self.turn),
+            chess_lookup::BACKRANK[self.turn],
+        );
+        let (_, dest) = Self::castle_targets(self.turn, side);
+
+        (source, dest)
+    }
+}
+
+#[inline]
+const fn castle_king_file(side: Side) -> File {
+    match side {
+        Side::King => File::G,
+        Side::Queen => File::C,
     }
 }
@@ -0,0 +1,90 @@
+use chess_bitboard::{Color, File, Piece, Pos, Side};
+
+use crate::{castle_rights::CastleRights, Board};
+
+/// The squares of both knights among the five squares left after placing the bishops and queen
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+impl Board {
+    /// The Chess960 starting position numbered `index` in Scharnagl's numbering, from 0 to 959.
+    ///
+    /// Position 518 is the standard starting position
+    pub fn chess960(index: u16) -> Option<Self> {
+        if index >= 960 {
+            return None;
+        }
+
+        let mut backrank = [None; 8];
+        let mut n = usize::from(index);
+
+        // the bishops on a light and on a dark square
+        backrank[n % 4 * 2 + 1] = Some(Piece::Bishop);
+        n /= 4;
+        backrank[n % 4 * 2] = Some(Piece::Bishop);
+        n /= 4;
+
+        place_on_empty(&mut backrank, n % 6, Piece::Queen);
+        n /= 6;
+
+        // the second knight first, so it doesn't move the square of the first one
+        let (first, second) = KNIGHTS[n];
+        place_on_empty(&mut backrank, second, Piece::Knight);
+        place_on_empty(&mut backrank, first, Piece::Knight);
+
+        // the king goes between the rooks
+        for piece in [Piece::Rook, Piece::King, Piece::Rook] {
+            place_on_empty(&mut backrank, 0, piece);
+        }
+
+        let mut builder = Board::builder();
+        let mut castle_rights = CastleRights::empty();
+
+        for (file, piece) in File::all().zip(backrank) {
+            let piece = piece.unwrap();
+
+            for color in Color::all() {
+                let rank = chess_lookup::BACKRANK[color];
+                let pawns = chess_lookup::PAWN_DOUBLE_MOVE_SOURCE_RANK[color];
+
+                builder.place(Pos::new(file, rank), color, piece).ok()?;
+                builder
+                    .place(Pos::new(file, pawns), color, Piece::Pawn)
+                    .ok()?;
+
+                // the first rook is on the queen side
+                if piece == Piece::Rook {
+                    let side = if castle_rights.contains(Side::Queen, color) {
+                        Side::King
+                    } else {
+                        Side::Queen
+                    };
+                    castle_rights.add_rook(side, color, file);
+                }
+            }
+        }
+
+        builder.castle_rights(castle_rights).full_move_clock(1);
+        builder.build().ok()
+    }
+}
+
+/// Put `piece` on the `n`th empty square
+fn place_on_empty(backrank: &mut [Option<Piece>; 8], n: usize, piece: Piece) {
+    let square = backrank
+        .iter_mut()
+        .filter(|sq| sq.is_none())
+        .nth(n)
+        .unwrap();
+    *square = Some(piece);
+}
@@ -1,6 +1,6 @@
-use chess_bitboard::{Color, File, Piece, Pos, Rank, Side};
+use chess_bitboard::{BitBoard, Color, File, Piece, Pos, Rank, Side};
 
-use crate::{castle_rights::CastleRights, raw};
+use crate::{castle_rights::CastleRights, raw, Board};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseFenError {
@@ -94,6 +94,26 @@ impl core::fmt::Display for ParseFenError {
 
 impl std::error::Error for ParseFenError {}
 
+/// Formats a board as Shredder-FEN, which writes the castle rights as the files of the rooks,
+/// like `HAha`, see [`Board::shredder_fen`]. The board's [`Display`](core::fmt::Display) writes
+/// X-FEN instead, which is the same as FEN for standard chess
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShredderFen<'a>(pub &'a Board);
+
+impl core::fmt::Display for ShredderFen<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.write_fen(f, true)
+    }
+}
+
+impl Board {
+    /// Formats the board as Shredder-FEN
+    #[inline]
+    pub fn shredder_fen(&self) -> ShredderFen<'_> {
+        ShredderFen(self)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MissingWhitespace {
     Pieces,
@@ -110,6 +130,7 @@ pub fn parse_fen(mut s: &[u8]) -> Result<crate::Board, ParseFenError> {
 
     let mut board = raw::RawBoard::empty();
     let mut zobrist = 0;
+    let mut pawn_zobrist = 0;
 
     loop {
         let (out, rest) = parse_piece(s);
@@ -124,6 +145,9 @@ pub fn parse_fen(mut s: &[u8]) -> Result<crate::Board, ParseFenError> {
                 #[cfg(not(test))]
                 board.set_unchecked(color, piece, pos);
                 zobrist ^= chess_lookup::zobrist(pos, piece, color);
+                if piece == Piece::Pawn {
+                    pawn_zobrist ^= chess_lookup::zobrist(pos, piece, color);
+                }
                 1
             }
             Some(Err(dist)) => dist,
@@ -148,9 +172,7 @@ pub fn parse_fen(mut s: &[u8]) -> Result<crate::Board, ParseFenError> {
                 //
             }
             8 => {
-                let Some(r) = ranks.next() else {
-                    break
-                };
+                let Some(r) = ranks.next() else { break };
 
                 file = 0;
                 rank = r;
@@ -169,32 +191,7 @@ pub fn parse_fen(mut s: &[u8]) -> Result<crate::Board, ParseFenError> {
 
     s = parse_whitespace(s, MissingWhitespace::Turn)?;
 
-    let mut castle_rights = CastleRights::empty();
-
-    let (white_king_cr, s) = parse_castle_rights(s, b'K');
-    let (white_queen_cr, s) = parse_castle_rights(s, b'Q');
-    let (black_king_cr, s) = parse_castle_rights(s, b'k');
-    let (black_queen_cr, mut s) = parse_castle_rights(s, b'q');
-
-    if white_king_cr {
-        castle_rights.add(Side::King, Color::White);
-    }
-
-    if white_queen_cr {
-        castle_rights.add(Side::Queen, Color::White);
-    }
-
-    if black_king_cr {
-        castle_rights.add(Side::King, Color::Black);
-    }
-
-    if black_queen_cr {
-        castle_rights.add(Side::Queen, Color::Black);
-    }
-
-    if !(white_king_cr || white_queen_cr || black_king_cr || black_queen_cr) {
-        s = parse_dash(s).ok_or(ParseFenError::MissingCastleRights)?;
-    }
+    let (castle_rights, mut s) = parse_castle_rights(s, &board)?;
 
     s = parse_whitespace(s, MissingWhitespace::CastleRights)?;
 
@@ -224,6 +221,7 @@ pub fn parse_fen(mut s: &[u8]) -> Result<crate::Board, ParseFenError> {
 
     let mut board = crate::Board {
         zobrist,
+        pawn_zobrist,
         raw: board,
         turn,
         pinned: chess_bitboard::BitBoard::empty(),
@@ -289,12 +287,75 @@ fn parse_dash(s: &[u8]) -> Option<&[u8]> {
     }
 }
 
-#[inline]
-fn parse_castle_rights(s: &[u8], b: u8) -> (bool, &[u8]) {
-    match s {
-        [x, s @ ..] if *x == b => (true, s),
-        _ => (false, s),
+/// Parses the castle rights as in X-FEN, where `KQkq` are the outermost rooks and other rooks
+/// are written as their file, or in Shredder-FEN, where every rook is written as its file
+fn parse_castle_rights<'a>(
+    mut s: &'a [u8],
+    board: &raw::RawBoard,
+) -> Result<(CastleRights, &'a [u8]), ParseFenError> {
+    let mut castle_rights = CastleRights::empty();
+
+    if let Some(s) = parse_dash(s) {
+        return Ok((castle_rights, s));
+    }
+
+    while let &[b @ (b'K' | b'Q' | b'k' | b'q' | b'A'..=b'H' | b'a'..=b'h'), ref rest @ ..] = s {
+        s = rest;
+
+        let color = if b.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        let backrank = BitBoard::from_rank(chess_lookup::BACKRANK[color]);
+        let Some(king) = (board[color] & board[Piece::King] & backrank).iter().next() else {
+            return Err(ParseFenError::BoardValidation(
+                crate::BoardValidationError::InvalidCastleRights,
+            ));
+        };
+
+        let rooks = board[color] & board[Piece::Rook] & backrank;
+        let (side, file) = match b.to_ascii_uppercase() {
+            b'K' => (
+                Side::King,
+                rooks
+                    .iter()
+                    .map(Pos::file)
+                    .filter(|&file| file > king.file())
+                    .max(),
+            ),
+            b'Q' => (
+                Side::Queen,
+                rooks
+                    .iter()
+                    .map(Pos::file)
+                    .filter(|&file| file < king.file())
+                    .min(),
+            ),
+            b => {
+                let file = File::from_u8(b - b'A').unwrap();
+                let side = if file > king.file() {
+                    Side::King
+                } else {
+                    Side::Queen
+                };
+                (side, Some(file))
+            }
+        };
+
+        // without a rook the rights are invalid, which validating the board reports
+        match file {
+            Some(file) => castle_rights.add_rook(side, color, file),
+            None => castle_rights.add(side, color),
+        }
     }
+
+    if castle_rights == CastleRights::empty() {
+        return Err(ParseFenError::MissingCastleRights);
+    }
+
+    Ok((castle_rights, s))
 }
 
 #[inline]
@@ -0,0 +1,259 @@
+use chess_bitboard::{BitBoard, Color, Piece};
+
+use crate::{
+    pgn::{GameResult, Pgn},
+    Board, ChessMove, MoveKind,
+};
+
+/// a1, c1, ..., b2, d2, ...
+const DARK_SQUARES: BitBoard = BitBoard::from_u64(0xAA55_AA55_AA55_AA55);
+
+/// How a game ended, see [`Game::outcome`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Checkmate { winner: Color },
+    Draw(DrawReason),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    Stalemate,
+    /// Neither side has enough material to checkmate
+    InsufficientMaterial,
+    /// Neither side can checkmate with any sequence of legal moves
+    DeadPosition,
+    /// The position occurred five times, which ends the game without a claim
+    FivefoldRepetition,
+    /// 75 moves by each side without a capture or pawn move
+    SeventyFiveMoves,
+    /// The position occurred three times, which a player may claim as a draw
+    ThreefoldRepetition,
+    /// 50 moves by each side without a capture or pawn move, which a player may claim as a draw
+    FiftyMoves,
+}
+
+impl DrawReason {
+    /// Whether a player has to claim the draw, instead of the game ending by itself
+    pub fn is_claimed(self) -> bool {
+        matches!(
+            self,
+            DrawReason::ThreefoldRepetition | DrawReason::FiftyMoves
+        )
+    }
+}
+
+impl Outcome {
+    pub fn result(self) -> GameResult {
+        match self {
+            Outcome::Checkmate { winner } => GameResult::win(winner),
+            Outcome::Draw(_) => GameResult::Draw,
+        }
+    }
+}
+
+impl core::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Checkmate { winner } => write!(f, "{winner:?} wins by checkmate"),
+            Outcome::Draw(reason) => write!(f, "draw by {reason}"),
+        }
+    }
+}
+
+impl core::fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::DeadPosition => "dead position",
+            DrawReason::FivefoldRepetition => "fivefold repetition",
+            DrawReason::SeventyFiveMoves => "the seventy-five-move rule",
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+            DrawReason::FiftyMoves => "the fifty-move rule",
+        })
+    }
+}
+
+impl Board {
+    /// Whether only kings are left, or a king with a single knight or bishop against a bare king
+    pub fn has_insufficient_material(&self) -> bool {
+        let raw = &self.raw;
+
+        if (raw[Piece::Queen] | raw[Piece::Rook] | raw[Piece::Pawn]).any() {
+            return false;
+        }
+
+        let bishops = raw[Piece::Bishop].count();
+        let knights = raw[Piece::Knight].count();
+
+        knights <= 1 && bishops == 0 || knights == 0 && bishops <= 1
+    }
+
+    /// The position as compared by the repetition rules, which ignore the en passant square
+    /// unless a pawn can actually capture en passant
+    fn repetition_key(&self) -> Board {
+        let mut board = *self;
+        if board.ep().is_some()
+            && !board
+                .legals()
+                .any(|mv| board.move_kind(mv) == MoveKind::EnPassant)
+        {
+            board.enpassant_target = None.into();
+        }
+        board
+    }
+
+    /// Whether no sequence of legal moves can end in checkmate.
+    ///
+    /// Besides insufficient material, this finds positions where the only pieces besides the
+    /// kings are bishops on squares of the same color. Blocked pawn chains aren't detected
+    pub fn is_dead_position(&self) -> bool {
+        let raw = &self.raw;
+        let bishops = raw[Piece::Bishop];
+
+        self.has_insufficient_material()
+            || (raw.all() == (bishops | raw[Piece::King])
+                && ((bishops & DARK_SQUARES).none() || (bishops & !DARK_SQUARES).none()))
+    }
+}
+
+/// A game from a starting position, with the moves played so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    /// The starting position followed by the position after each move
+    boards: Vec<Board>,
+    /// [`Board::repetition_key`] of each position in `boards`
+    keys: Vec<Board>,
+    moves: Vec<ChessMove>,
+    /// How often each position in `boards` occurred up to that point
+    repetitions: Vec<u8>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::standard())
+    }
+}
+
+impl Game {
+    pub fn new(start: Board) -> Self {
+        Self {
+            boards: vec![start],
+            keys: vec![start.repetition_key()],
+            moves: Vec::new(),
+            repetitions: vec![1],
+        }
+    }
+
+    #[inline]
+    pub fn start(&self) -> &Board {
+        &self.boards[0]
+    }
+
+    /// The current position
+    #[inline]
+    pub fn board(&self) -> &Board {
+        self.boards.last().unwrap()
+    }
+
+    #[inline]
+    pub fn moves(&self) -> &[ChessMove] {
+        &self.moves
+    }
+
+    /// The starting position followed by the position after each move
+    #[inline]
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    /// How often the current position occurred, including now
+    #[inline]
+    pub fn repetitions(&self) -> u8 {
+        *self.repetitions.last().unwrap()
+    }
+
+    /// Play `mv`, returns false if it isn't legal
+    #[must_use]
+    pub fn push(&mut self, mv: ChessMove) -> bool {
+        let Some(board) = self.board().move_new(mv) else {
+            return false;
+        };
+
+        // a position can only repeat with the same side to move, and captures and pawn moves
+        // make the earlier positions unreachable
+        let reversible = board.half_move_clock() as usize;
+        let key = board.repetition_key();
+        let repetitions = self
+            .keys
+            .iter()
+            .zip(&self.repetitions)
+            .rev()
+            .take(reversible)
+            .skip(1)
+            .step_by(2)
+            .find(|&(old, _)| *old == key)
+            .map_or(1, |(_, &count)| count.saturating_add(1));
+
+        self.boards.push(board);
+        self.keys.push(key);
+        self.moves.push(mv);
+        self.repetitions.push(repetitions);
+        true
+    }
+
+    /// Take back the last move
+    pub fn pop(&mut self) -> Option<ChessMove> {
+        let mv = self.moves.pop()?;
+        self.boards.pop();
+        self.keys.pop();
+        self.repetitions.pop();
+        Some(mv)
+    }
+
+    /// How the game ended, or `None` if it is still going.
+    ///
+    /// Draws which have to be claimed, see [`DrawReason::is_claimed`], are reported as well
+    pub fn outcome(&self) -> Option<Outcome> {
+        let board = self.board();
+
+        if board.legals().is_empty() {
+            return Some(if board.in_check() {
+                Outcome::Checkmate {
+                    winner: !board.turn(),
+                }
+            } else {
+                Outcome::Draw(DrawReason::Stalemate)
+            });
+        }
+
+        let reason = if board.has_insufficient_material() {
+            DrawReason::InsufficientMaterial
+        } else if board.is_dead_position() {
+            DrawReason::DeadPosition
+        } else if self.repetitions() >= 5 {
+            DrawReason::FivefoldRepetition
+        } else if board.half_move_clock() >= 150 {
+            DrawReason::SeventyFiveMoves
+        } else if self.repetitions() >= 3 {
+            DrawReason::ThreefoldRepetition
+        } else if board.half_move_clock() >= 100 {
+            DrawReason::FiftyMoves
+        } else {
+            return None;
+        };
+
+        Some(Outcome::Draw(reason))
+    }
+
+    /// The game as PGN, with the result set from [`Game::outcome`]
+    pub fn to_pgn(&self) -> Pgn {
+        let mut pgn = Pgn::new(*self.start());
+        for &mv in &self.moves {
+            assert!(pgn.push(mv));
+        }
+
+        pgn.result = self.outcome().map_or(GameResult::Unknown, Outcome::result);
+        pgn
+    }
+}
@@ -100,22 +100,6 @@ impl Board {
 
         moves
     }
-
-    pub fn perft_test(&self, depth: usize) -> usize {
-        let iterable = self.legals();
-
-        let mut result: usize = 0;
-        if depth == 1 {
-            iterable.len()
-        } else {
-            let mut next_board = Board::standard();
-            for m in iterable {
-                unsafe { self.move_unchecked_into(m, &mut next_board) };
-                result += next_board.perft_test(depth - 1);
-            }
-            result
-        }
-    }
 }
 
 impl MoveGen {
@@ -158,8 +142,9 @@ impl MoveGen {
     pub fn remove_move(&mut self, chess_move: ChessMove) -> bool {
         for x in 0..self.moves.len() {
             if self.moves[x].src == chess_move.source {
+                let contains = self.moves[x].moves.contains(chess_move.dest);
                 self.moves[x].moves -= chess_move.dest;
-                return true;
+                return contains;
             }
         }
         false
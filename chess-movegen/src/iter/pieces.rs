@@ -142,10 +142,28 @@ impl PieceType for Pawn {
 
             // if the opponent's pawn is checking the king (and the only piece checking the king)
             // or if the there is no check and the opponent's pawn doesn't block a check against our king
-            // then we can capture it via en-passant with any unpinned pawn on the same rank and adjacent file as the
-            // opponent's pawn
-            if check_mask.contains(capture_pawn) && !board.pinned.contains(capture_pawn) {
-                for src in BitBoard::from(rank) & files & pieces & !board.pinned {
+            // then we can capture it via en-passant with any pawn on the same rank and adjacent file as the
+            // opponent's pawn. A pawn pinned along its file stays pinned by the capturing pawn
+            let pinned = board.pinned.contains(capture_pawn) && king_sq.file() != ep_file;
+            if check_mask.contains(capture_pawn) && !pinned {
+                let rank_sliders = (board.raw[Piece::Rook] | board.raw[Piece::Queen])
+                    & board.raw[!board.turn]
+                    & BitBoard::from(rank);
+
+                for src in BitBoard::from(rank) & files & pieces {
+                    // a pinned pawn can only capture along the pin
+                    if board.pinned.contains(src)
+                        && (chess_lookup::line(king_sq, src) & dest).none()
+                    {
+                        continue;
+                    }
+
+                    // both pawns leave the rank, which may open it between the king and a rook
+                    let occupied = combined ^ BitBoard::from(src) ^ BitBoard::from(capture_pawn);
+                    if (chess_lookup::rook_moves(king_sq, occupied) & rank_sliders).any() {
+                        continue;
+                    }
+
                     unsafe {
                         movelist.push_unchecked(LegalMovesAt {
                             src,
@@ -227,39 +245,38 @@ impl King {
         }
 
         if !IS_IN_CHECK {
-            let data = [
-                (
-                    Side::King,
-                    chess_lookup::KINGSIDE_CASTLE_FILES,
-                    chess_lookup::KINGSIDE_CASTLE_SAFE_FILES,
-                ),
-                (
-                    Side::Queen,
-                    chess_lookup::QUEENSIDE_CASTLE_FILES,
-                    chess_lookup::QUEENSIDE_CASTLE_SAFE_FILES,
-                ),
-            ];
-
-            for (side, castle_files, castle_safe_files) in data {
+            for side in Side::all() {
                 if !board.castle_rights.contains(side, turn) {
                     continue;
                 }
 
-                let backrank = chess_lookup::BACKRANK_BB[turn];
-                let castle_tiles = castle_files & backrank;
+                let rook_sq = Pos::new(
+                    board.castle_rights.rook_file(side, turn),
+                    chess_lookup::BACKRANK[turn],
+                );
+                let (king_dest, rook_dest) = Board::castle_targets(turn, side);
 
-                if board.castle_rights.contains(side, turn) && (castle_tiles & combined).none() {
-                    let no_check_sq = castle_safe_files & backrank;
+                // only the king and the rook may be in the way
+                let castlers = BitBoard::from(king_sq) | BitBoard::from(rook_sq);
+                let king_path =
+                    chess_lookup::between(king_sq, king_dest) | BitBoard::from(king_dest);
+                let rook_path =
+                    chess_lookup::between(rook_sq, rook_dest) | BitBoard::from(rook_dest);
 
-                    debug_assert_eq!(no_check_sq.count(), 2);
+                if ((king_path | rook_path) & combined & !castlers).any() {
+                    continue;
+                }
 
-                    if no_check_sq
-                        .iter()
-                        .all(|dest| board.is_legal_king_position(dest))
-                    {
-                        moves ^= castle_tiles & chess_lookup::CASTLE_MOVES
-                    }
+                // the rook could be blocking an attack on the king's destination
+                let occupied = combined ^ castlers;
+                if king_path
+                    .iter()
+                    .any(|pos| board.raw.is_attacked(pos, !turn, occupied))
+                {
+                    continue;
                 }
+
+                moves.set(board.castle_move_dest(turn, side));
             }
         }
 
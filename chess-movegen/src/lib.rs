@@ -1,9 +1,25 @@
 #![forbid(unsafe_op_in_unsafe_fn)]
 
+mod attacks;
 mod castle_rights;
+mod chess960;
 pub mod fen;
+mod game;
 mod iter;
+mod move_kind;
+mod perft;
+pub mod pgn;
 pub mod raw;
+pub mod san;
+mod see;
+pub mod uci;
+
+pub use attacks::piece_attacks;
+pub use game::{DrawReason, Game, Outcome};
+pub use iter::MoveGen;
+pub use move_kind::MoveKind;
+pub use perft::{PerftStats, PerftTable};
+pub use see::SEE_VALUES;
 
 use std::{
     fmt::{Debug, Write},
@@ -19,6 +35,7 @@ use chess_bitboard::{BitBoard, Color, File, Piece, Pos, PromotionPiece, Rank, Si
 #[derive(Clone, Copy)]
 pub struct Board {
     zobrist: u64,
+    pawn_zobrist: u64,
     turn: Color,
     castle_rights: castle_rights::CastleRights,
     // We use `OptionalFile` instead of `Option<File>` because
@@ -114,19 +131,34 @@ impl FromStr for ChessMove {
 }
 
 impl ChessMove {
+    /// Passing the turn, which is written as `0000` in long algebraic notation
+    pub const NULL: ChessMove = ChessMove {
+        source: Pos::A1,
+        dest: Pos::A1,
+        piece: None,
+    };
+
+    /// Parses `e2e4`, `e2-e4`, the null move `0000` and promotions like `e7e8q` or `e7-e8Q`
     pub fn from_ascii_bytes(s: &[u8]) -> Option<Self> {
-        match *s {
-            [sf, sr, b'-', df, dr] | [sf, sr, df, dr] => {
-                let source = Pos::from_ascii_bytes(&[sf, sr])?;
-                let dest = Pos::from_ascii_bytes(&[df, dr])?;
-                Some(ChessMove {
-                    source,
-                    dest,
-                    piece: None,
-                })
+        let (sf, sr, df, dr, promotion) = match *s {
+            [b'0', b'0', b'0', b'0'] => return Some(Self::NULL),
+            [sf, sr, b'-', df, dr, ref promotion @ ..] | [sf, sr, df, dr, ref promotion @ ..] => {
+                (sf, sr, df, dr, promotion)
             }
-            _ => None,
-        }
+            _ => return None,
+        };
+
+        let piece = match *promotion {
+            [] => None,
+            [piece] => Some(PromotionPiece::from_ascii_byte(piece)?),
+            _ => return None,
+        };
+
+        Some(ChessMove {
+            source: Pos::from_ascii_bytes(&[sf, sr])?,
+            dest: Pos::from_ascii_bytes(&[df, dr])?,
+            piece,
+        })
     }
 }
 
@@ -141,7 +173,15 @@ impl core::fmt::Display for ChessMove {
 }
 
 impl core::fmt::Display for Board {
+    /// Writes the board as FEN, with the castle rights as in X-FEN
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_fen(f, false)
+    }
+}
+
+impl Board {
+    /// Writes the board as FEN, with the castle rights as in Shredder-FEN or X-FEN
+    fn write_fen(&self, f: &mut std::fmt::Formatter<'_>, shredder: bool) -> std::fmt::Result {
         static PIECES: [[char; 6]; 2] = [
             ['P', 'N', 'B', 'R', 'Q', 'K'],
             ['p', 'n', 'b', 'r', 'q', 'k'],
@@ -179,7 +219,7 @@ impl core::fmt::Display for Board {
             Color::Black => " b ",
         })?;
 
-        self.castle_rights.fmt(f)?;
+        self.write_castle_rights(f, shredder)?;
 
         match self.ep() {
             Some(file) => {
@@ -314,6 +354,9 @@ impl BoardBuilder {
     ) -> Result<&mut Self, raw::PieceAlreadyExists> {
         self.board.raw.set(color, piece, pos)?;
         self.board.zobrist ^= chess_lookup::zobrist(pos, piece, color);
+        if piece == Piece::Pawn {
+            self.board.pawn_zobrist ^= chess_lookup::zobrist(pos, piece, color);
+        }
         Ok(self)
     }
 
@@ -321,6 +364,9 @@ impl BoardBuilder {
     pub fn remove(&mut self, pos: Pos) -> &mut Self {
         if let Some((color, piece)) = self.board.raw.get(pos) {
             self.board.zobrist ^= chess_lookup::zobrist(pos, piece, color);
+            if piece == Piece::Pawn {
+                self.board.pawn_zobrist ^= chess_lookup::zobrist(pos, piece, color);
+            }
             self.board.raw.remove(color, piece, pos);
         }
 
@@ -357,6 +403,7 @@ impl Board {
         BoardBuilder {
             board: Self {
                 zobrist: 0,
+                pawn_zobrist: 0,
                 turn: Color::White,
                 castle_rights: castle_rights::CastleRights::empty(),
                 enpassant_target: OptionalFile::None,
@@ -377,11 +424,12 @@ impl Board {
     pub const fn standard() -> Self {
         Self {
             zobrist: 9406092833587483707,
+            pawn_zobrist: 16750484712350136322,
             turn: Color::White,
             castle_rights: castle_rights::CastleRights::full(),
             enpassant_target: OptionalFile::None,
             half_move_clock: 0,
-            full_move_clock: 0,
+            full_move_clock: 1,
             pinned: BitBoard::empty(),
             checkers: BitBoard::empty(),
             raw: raw::RawBoard::standard(),
@@ -431,36 +479,34 @@ impl Board {
         Ok(())
     }
 
+    /// Every side which may castle needs the king on the back rank and the rook between the
+    /// king and that side of the board
     fn validate_castle_rights(&self) -> Result<(), BoardValidationError> {
         let cr = self.castle_rights;
-        if cr.contains(Side::Queen, Color::White)
-            && self.raw.get(Pos::A1) != Some((Color::White, Piece::Rook))
-        {
-            return Err(BoardValidationError::InvalidCastleRights);
-        }
 
-        if cr.contains(Side::King, Color::Black)
-            && self.raw.get(Pos::H8) != Some((Color::Black, Piece::Rook))
-        {
-            return Err(BoardValidationError::InvalidCastleRights);
-        }
+        for color in Color::all() {
+            let king = self.king_sq(color);
+            let backrank = chess_lookup::BACKRANK[color];
 
-        if cr.contains(Side::Queen, Color::Black)
-            && self.raw.get(Pos::A8) != Some((Color::Black, Piece::Rook))
-        {
-            return Err(BoardValidationError::InvalidCastleRights);
-        }
+            for side in Side::all() {
+                if !cr.contains(side, color) {
+                    continue;
+                }
 
-        if cr.contains_color(Color::White)
-            && self.raw.get(Pos::E1) != Some((Color::White, Piece::King))
-        {
-            return Err(BoardValidationError::InvalidCastleRights);
-        }
+                let rook = Pos::new(cr.rook_file(side, color), backrank);
+                let rook_side = match rook.file().cmp(&king.file()) {
+                    std::cmp::Ordering::Greater => Some(Side::King),
+                    std::cmp::Ordering::Less => Some(Side::Queen),
+                    std::cmp::Ordering::Equal => None,
+                };
 
-        if cr.contains_color(Color::Black)
-            && self.raw.get(Pos::E8) != Some((Color::Black, Piece::King))
-        {
-            return Err(BoardValidationError::InvalidCastleRights);
+                if king.rank() != backrank
+                    || self.raw.get(rook) != Some((color, Piece::Rook))
+                    || rook_side != Some(side)
+                {
+                    return Err(BoardValidationError::InvalidCastleRights);
+                }
+            }
         }
 
         Ok(())
@@ -486,6 +532,12 @@ impl Board {
         self.full_move_clock
     }
 
+    /// A zobrist key of only the pawns, for caching evaluations of the pawn structure
+    #[inline]
+    pub fn pawn_zobrist(&self) -> u64 {
+        self.pawn_zobrist
+    }
+
     #[inline]
     pub fn zobrist(&self) -> u64 {
         self.zobrist
@@ -558,8 +610,10 @@ impl Board {
         self.checkers |= pawn_attacks;
     }
 
+    /// The square a pawn of the side to move captures onto en passant, if the last move was a
+    /// double pawn push
     #[inline]
-    fn enpassant_pos(&self) -> Option<Pos> {
+    pub fn enpassant_pos(&self) -> Option<Pos> {
         self.ep()
             .map(|file| Pos::new(file, self.turn.enpassant_capture_rank()))
     }
@@ -596,6 +650,22 @@ impl Board {
         }
     }
 
+    /// Pass the turn to the other side without moving, which is only possible when not in check
+    pub fn null_move(&self) -> Option<Self> {
+        if self.in_check() {
+            return None;
+        }
+
+        let mut board = *self;
+        board.turn = !self.turn;
+        board.enpassant_target = OptionalFile::None;
+        board.half_move_clock += 1;
+        board.full_move_clock += self.turn as u16;
+        board.update_pin_info();
+
+        Some(board)
+    }
+
     /// # Safety
     ///
     /// * There must be a piece at mv.start
@@ -633,7 +703,30 @@ impl Board {
     /// * The color of mv.end must be !self.turn or an empty tile
     /// * mv.promotion must only be set if the moved piece is a pawn and it is moving to the promotion rank
     /// * mv must be a legal chess move
+    #[inline]
     pub unsafe fn move_unchecked_into(&self, mv: ChessMove, output: &mut Self) {
+        unsafe { self.move_unchecked_observed(mv, output, |_, _, _| ()) }
+    }
+
+    /// Like [`Board::move_unchecked_into`], and calls `observer` for every change to the pieces:
+    /// the squares in the [`BitBoard`] toggle between having a piece of that color and kind and
+    /// being empty. A square may change more than once in a move, e.g. when promoting
+    ///
+    /// # Safety
+    ///
+    /// The same as [`Board::move_unchecked_into`]
+    #[inline]
+    pub unsafe fn move_unchecked_observed(
+        &self,
+        mv: ChessMove,
+        output: &mut Self,
+        mut observer: impl FnMut(Color, Piece, BitBoard),
+    ) {
+        let mut xor = |output: &mut Self, color, piece, diff| {
+            output.xor(color, piece, diff);
+            observer(color, piece, diff);
+        };
+
         *output = *self;
         output.enpassant_target = OptionalFile::None;
         output.checkers = BitBoard::empty();
@@ -645,11 +738,34 @@ impl Board {
         let mv_bb = source_bb ^ dest_bb;
 
         let piece = unsafe { self.raw.piece_of_unchecked(mv.source) };
-        let captured = self.raw.piece_of(mv.dest);
+        let castles = self.castle_side(mv);
+        let captured = match castles {
+            Some(_) => None,
+            None => self.raw.piece_of(mv.dest),
+        };
+
+        if let Some(side) = castles {
+            let (king_dest, rook_dest) = Self::castle_targets(self.turn, side);
+            let (rook_source, _) = self.castle_rook_move(side);
+
+            xor(
+                output,
+                self.turn,
+                Piece::King,
+                source_bb ^ BitBoard::from(king_dest),
+            );
+            xor(
+                output,
+                self.turn,
+                Piece::Rook,
+                BitBoard::from(rook_source) ^ BitBoard::from(rook_dest),
+            );
+        } else {
+            xor(output, self.turn, piece, mv_bb);
+        }
 
-        output.xor(self.turn, piece, mv_bb);
         if let Some(captured) = captured {
-            output.xor(!self.turn, captured, dest_bb);
+            xor(output, !self.turn, captured, dest_bb);
             output.half_move_clock = 0;
         } else {
             output.half_move_clock += 1;
@@ -658,9 +774,11 @@ impl Board {
 
         output.castle_rights.remove_for_sq(!self.turn, mv.dest);
         output.castle_rights.remove_for_sq(self.turn, mv.source);
+        if piece == Piece::King {
+            output.castle_rights.remove_color(self.turn);
+        }
 
         let opp_king = self.king_sq(!self.turn);
-        let castles = piece == Piece::King && (mv_bb & chess_lookup::CASTLE_MOVES) == mv_bb;
 
         if piece == Piece::Knight {
             output.checkers ^= chess_lookup::knight_moves(opp_king) & dest_bb;
@@ -674,15 +792,16 @@ impl Board {
                     output.checkers ^= chess_lookup::knight_moves(opp_king) & dest_bb;
                 }
 
-                output.xor(self.turn, Piece::Pawn, dest_bb);
-                output.xor(self.turn, promotion.to_piece(), dest_bb);
+                xor(output, self.turn, Piece::Pawn, dest_bb);
+                xor(output, self.turn, promotion.to_piece(), dest_bb);
             } else if mv_bb & chess_lookup::PAWN_DOUBLE_MOVE[self.turn] == mv_bb {
                 output.enpassant_target = Some(mv.dest.file()).into();
             } else if Some(mv.dest) == self.enpassant_pos() {
                 let ep_file = mv.dest.file();
 
                 // remove pawn by en-passant
-                output.xor(
+                xor(
+                    output,
                     !self.turn,
                     Piece::Pawn,
                     BitBoard::from_pos(Pos::new(ep_file, self.turn.enpassant_pawn_rank())),
@@ -692,14 +811,6 @@ impl Board {
             if mv.piece.is_none() {
                 output.checkers ^= chess_lookup::pawn_attacks_moves(opp_king, !self.turn) & dest_bb;
             }
-        } else if castles {
-            let rook_mv = chess_lookup::BACKRANK_BB[self.turn]
-                & match mv.dest.file().side() {
-                    Side::King => chess_lookup::ROOK_CASTLE_KINGSIDE,
-                    Side::Queen => chess_lookup::ROOK_CASTLE_QUEENSIDE,
-                };
-
-            output.xor(self.turn, Piece::Rook, rook_mv);
         }
 
         let pieces = output.raw[self.turn];
@@ -730,6 +841,12 @@ impl Board {
         for pos in diff {
             self.zobrist ^= chess_lookup::zobrist(pos, piece, color);
         }
+
+        if piece == Piece::Pawn {
+            for pos in diff {
+                self.pawn_zobrist ^= chess_lookup::zobrist(pos, piece, color);
+            }
+        }
     }
 }
 
@@ -753,16 +870,98 @@ impl Index<Piece> for Board {
 
 #[cfg(test)]
 mod tests {
+    use chess_bitboard::{BitBoard, Color, Piece, Pos, PromotionPiece};
+
     use crate::Board;
 
     #[test]
     fn test_standard_is_correct() {
         let board = Board::standard();
-        let standard = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0"
+        let standard: Board = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
             .parse()
             .unwrap();
 
         assert_eq!(board, standard);
+        assert_eq!(board.full_move_clock, standard.full_move_clock);
         assert_eq!(board.zobrist, standard.zobrist);
+        assert_eq!(board.pawn_zobrist, standard.pawn_zobrist);
+    }
+
+    #[test]
+    fn test_pawn_zobrist() {
+        let mv = |source, dest, piece| crate::ChessMove {
+            source,
+            dest,
+            piece,
+        };
+
+        let board: Board = "4k3/1P6/8/3pP3/8/8/8/R3K2N w - d6 0 1".parse().unwrap();
+
+        // moving other pieces doesn't change the pawn key
+        let knight = board.move_new(mv(Pos::H1, Pos::G3, None)).unwrap();
+        assert_eq!(knight.pawn_zobrist(), board.pawn_zobrist());
+        assert_ne!(knight.zobrist(), board.zobrist());
+
+        let cases = [
+            (
+                mv(Pos::E5, Pos::D6, None),
+                "4k3/1P6/3P4/8/8/8/8/R3K2N b - - 0 1",
+            ),
+            (
+                mv(Pos::B7, Pos::B8, Some(PromotionPiece::Queen)),
+                "1Q2k3/8/8/3pP3/8/8/8/R3K2N b - - 0 1",
+            ),
+        ];
+        for (mv, fen) in cases {
+            let expected: Board = fen.parse().unwrap();
+            let board = board.move_new(mv).unwrap();
+            assert_eq!(board.pawn_zobrist(), expected.pawn_zobrist());
+            assert_eq!(board.zobrist(), expected.zobrist());
+        }
+    }
+
+    #[test]
+    fn test_observed_moves() {
+        let board: Board = "r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1"
+            .parse()
+            .unwrap();
+
+        for mv in board.legals() {
+            let mut pieces = [[BitBoard::empty(); 6]; 2];
+            for color in Color::all() {
+                for piece in Piece::all() {
+                    pieces[color][piece] = board[color] & board[piece];
+                }
+            }
+
+            let mut output = board;
+            unsafe {
+                board.move_unchecked_observed(mv, &mut output, |color, piece, diff| {
+                    pieces[color][piece] ^= diff;
+                })
+            };
+
+            assert_eq!(Some(output), board.move_new(mv));
+            for color in Color::all() {
+                for piece in Piece::all() {
+                    assert_eq!(pieces[color][piece], output[color] & output[piece], "{mv}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_null_move() {
+        let board: Board = "4k3/8/8/8/1b6/8/3P4/4K3 b - - 0 1".parse().unwrap();
+        let null = board.null_move().unwrap();
+
+        let expected: Board = "4k3/8/8/8/1b6/8/3P4/4K3 w - - 1 2".parse().unwrap();
+        assert_eq!(null, expected);
+        assert_eq!(null.zobrist(), expected.zobrist());
+        // the pawn is pinned by the bishop
+        assert_eq!(null.legals().len(), expected.legals().len());
+
+        let check: Board = "4k3/8/8/8/1b6/8/8/4K3 w - - 0 1".parse().unwrap();
+        assert!(check.null_move().is_none());
     }
 }
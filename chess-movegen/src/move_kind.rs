@@ -0,0 +1,124 @@
+use chess_bitboard::{BitBoard, Piece, Pos, PromotionPiece, Side};
+
+use crate::{Board, ChessMove};
+
+/// What a move does, see [`Board::move_kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveKind {
+    /// Neither a capture nor a promotion, and not castling
+    Quiet,
+    Capture,
+    /// A pawn capturing a pawn which just moved past it
+    EnPassant,
+    Castle(Side),
+    Promotion(PromotionPiece),
+    PromotionCapture(PromotionPiece),
+}
+
+impl MoveKind {
+    #[inline]
+    pub fn is_capture(self) -> bool {
+        matches!(
+            self,
+            MoveKind::Capture | MoveKind::EnPassant | MoveKind::PromotionCapture(_)
+        )
+    }
+
+    #[inline]
+    pub fn is_promotion(self) -> bool {
+        matches!(self, MoveKind::Promotion(_) | MoveKind::PromotionCapture(_))
+    }
+
+    #[inline]
+    pub fn is_castle(self) -> bool {
+        matches!(self, MoveKind::Castle(_))
+    }
+}
+
+impl Board {
+    /// Classify `mv`, which must be a legal move
+    #[inline]
+    pub fn move_kind(&self, mv: ChessMove) -> MoveKind {
+        let capture = self.raw[!self.turn].contains(mv.dest);
+
+        match (mv.piece, capture) {
+            (Some(piece), false) => MoveKind::Promotion(piece),
+            (Some(piece), true) => MoveKind::PromotionCapture(piece),
+            (None, true) => MoveKind::Capture,
+            (None, false) => match self.raw.piece_of(mv.source) {
+                Some(Piece::Pawn) if Some(mv.dest) == self.enpassant_pos() => MoveKind::EnPassant,
+                Some(Piece::King) => match self.castle_side(mv) {
+                    Some(side) => MoveKind::Castle(side),
+                    None => MoveKind::Quiet,
+                },
+                _ => MoveKind::Quiet,
+            },
+        }
+    }
+
+    /// Whether `mv`, which must be a legal move, captures a piece, including en passant
+    #[inline]
+    pub fn is_capture(&self, mv: ChessMove) -> bool {
+        self.move_kind(mv).is_capture()
+    }
+
+    /// The piece `mv` captures, including the pawn captured en passant
+    #[inline]
+    pub fn captured_piece(&self, mv: ChessMove) -> Option<Piece> {
+        match self.move_kind(mv) {
+            MoveKind::EnPassant => Some(Piece::Pawn),
+            _ => self.raw.piece_of(mv.dest),
+        }
+    }
+
+    /// Whether `mv`, which must be a legal move, checks the other king, without making it.
+    ///
+    /// This includes checks by the moved or promoted piece, by the rook when castling, and
+    /// discovered checks by the pieces behind the moved piece or the pawn captured en passant
+    pub fn gives_check(&self, mv: ChessMove) -> bool {
+        let Some(piece) = self.raw.piece_of(mv.source) else {
+            return false;
+        };
+
+        let king = self.king_sq(!self.turn);
+        let ours = self.raw[self.turn];
+        let source = BitBoard::from(mv.source);
+        let dest = BitBoard::from(mv.dest);
+
+        let mut occupied = (self.raw.all() & !source) | dest;
+        let mut moved = source;
+
+        let (piece, pos) = match self.move_kind(mv) {
+            MoveKind::EnPassant => {
+                let pawn = Pos::new(mv.dest.file(), self.turn.enpassant_pawn_rank());
+                occupied ^= BitBoard::from(pawn);
+                (piece, mv.dest)
+            }
+            MoveKind::Castle(side) => {
+                let (king_dest, rook_dest) = Self::castle_targets(self.turn, side);
+                let (rook_source, _) = self.castle_rook_move(side);
+
+                occupied = self.raw.all() & !source & !BitBoard::from(rook_source);
+                occupied |= BitBoard::from(king_dest) | BitBoard::from(rook_dest);
+                moved |= BitBoard::from(rook_source);
+
+                (Piece::Rook, rook_dest)
+            }
+            MoveKind::Promotion(promotion) | MoveKind::PromotionCapture(promotion) => {
+                (promotion.to_piece(), mv.dest)
+            }
+            MoveKind::Quiet | MoveKind::Capture => (piece, mv.dest),
+        };
+
+        if crate::piece_attacks(self.turn, piece, pos, occupied).contains(king) {
+            return true;
+        }
+
+        // the pieces which didn't move may now see the king
+        let bishops = (self.raw[Piece::Bishop] | self.raw[Piece::Queen]) & ours & !moved;
+        let rooks = (self.raw[Piece::Rook] | self.raw[Piece::Queen]) & ours & !moved;
+
+        (chess_lookup::bishop_moves(king, occupied) & bishops).any()
+            || (chess_lookup::rook_moves(king, occupied) & rooks).any()
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{Board, ChessMove, MoveKind};
+
+/// What the moves at the last ply of a perft do, to narrow down which kind of move a movegen
+/// bug is in. The counts match the tables on the chessprogramming wiki
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PerftStats {
+    pub nodes: usize,
+    /// Including en passant
+    pub captures: usize,
+    pub en_passants: usize,
+    pub castles: usize,
+    pub promotions: usize,
+    /// Including checkmates
+    pub checks: usize,
+    pub checkmates: usize,
+}
+
+impl core::ops::AddAssign for PerftStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.nodes += rhs.nodes;
+        self.captures += rhs.captures;
+        self.en_passants += rhs.en_passants;
+        self.castles += rhs.castles;
+        self.promotions += rhs.promotions;
+        self.checks += rhs.checks;
+        self.checkmates += rhs.checkmates;
+    }
+}
+
+impl core::fmt::Display for PerftStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "nodes: {}", self.nodes)?;
+        writeln!(f, "captures: {}", self.captures)?;
+        writeln!(f, "en passants: {}", self.en_passants)?;
+        writeln!(f, "castles: {}", self.castles)?;
+        writeln!(f, "promotions: {}", self.promotions)?;
+        writeln!(f, "checks: {}", self.checks)?;
+        write!(f, "checkmates: {}", self.checkmates)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct PerftEntry {
+    key: u64,
+    depth: usize,
+    nodes: usize,
+}
+
+/// A fixed size hash table of perft results, indexed by [`Board::zobrist`] and replaced on every
+/// store, see [`Board::perft_hashed`]
+pub struct PerftTable {
+    entries: Box<[PerftEntry]>,
+}
+
+impl PerftTable {
+    /// Create a new table which uses roughly `size_mb` megabytes
+    pub fn new(size_mb: usize) -> Self {
+        let len = (size_mb * 1024 * 1024 / core::mem::size_of::<PerftEntry>()).max(1);
+
+        Self {
+            entries: vec![PerftEntry::default(); len].into_boxed_slice(),
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    fn entry(&mut self, key: u64) -> &mut PerftEntry {
+        let index = ((u128::from(key) * self.entries.len() as u128) >> 64) as usize;
+        &mut self.entries[index]
+    }
+}
+
+impl Board {
+    /// The number of leaf nodes `depth` plies below this position
+    pub fn perft_test(&self, depth: usize) -> usize {
+        match depth {
+            0 => 1,
+            1 => self.legals().len(),
+            depth => {
+                let mut result = 0;
+                let mut next_board = Board::standard();
+                for mv in self.legals() {
+                    unsafe { self.move_unchecked_into(mv, &mut next_board) };
+                    result += next_board.perft_test(depth - 1);
+                }
+                result
+            }
+        }
+    }
+
+    /// [`Board::perft_test`] split up by the legal moves of this position, the counts add up to
+    /// the total for `depth`. A depth of 0 has no moves to split by, so it is empty
+    pub fn perft_divide(&self, depth: usize) -> Vec<(ChessMove, usize)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        self.legals()
+            .map(|mv| (mv, self.perft_after(mv, depth - 1)))
+            .collect()
+    }
+
+    /// Like [`Board::perft_test`], but counts what the moves at the last ply do
+    pub fn perft_stats(&self, depth: usize) -> PerftStats {
+        let mut stats = PerftStats::default();
+        if depth == 0 {
+            stats.nodes = 1;
+            return stats;
+        }
+
+        let mut next_board = Board::standard();
+        for mv in self.legals() {
+            unsafe { self.move_unchecked_into(mv, &mut next_board) };
+
+            if depth > 1 {
+                stats += next_board.perft_stats(depth - 1);
+                continue;
+            }
+
+            stats.nodes += 1;
+            match self.move_kind(mv) {
+                MoveKind::Capture => stats.captures += 1,
+                MoveKind::EnPassant => {
+                    stats.captures += 1;
+                    stats.en_passants += 1;
+                }
+                MoveKind::Castle(_) => stats.castles += 1,
+                MoveKind::Promotion(_) => stats.promotions += 1,
+                MoveKind::PromotionCapture(_) => {
+                    stats.captures += 1;
+                    stats.promotions += 1;
+                }
+                MoveKind::Quiet => {}
+            }
+
+            if next_board.in_check() {
+                stats.checks += 1;
+                if next_board.legals().is_empty() {
+                    stats.checkmates += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Like [`Board::perft_test`], but looks up and stores the counts of the positions below the
+    /// root in `table`, so transpositions are only counted once. This is much faster on deep runs.
+    ///
+    /// A hash collision makes the count wrong, which is unlikely with a 64 bit key
+    pub fn perft_hashed(&self, depth: usize, table: &mut PerftTable) -> usize {
+        if depth <= 1 {
+            return self.perft_test(depth);
+        }
+
+        let key = self.zobrist();
+        let entry = *table.entry(key);
+        if entry.key == key && entry.depth == depth {
+            return entry.nodes;
+        }
+
+        let mut nodes = 0;
+        let mut next_board = Board::standard();
+        for mv in self.legals() {
+            unsafe { self.move_unchecked_into(mv, &mut next_board) };
+            nodes += next_board.perft_hashed(depth - 1, table);
+        }
+
+        *table.entry(key) = PerftEntry { key, depth, nodes };
+        nodes
+    }
+
+    /// [`Board::perft_divide`] with the root moves split between the threads of rayon's pool
+    #[cfg(feature = "rayon")]
+    pub fn perft_divide_parallel(&self, depth: usize) -> Vec<(ChessMove, usize)> {
+        use rayon::prelude::*;
+
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        let moves: Vec<_> = self.legals().collect();
+        moves
+            .into_par_iter()
+            .map(|mv| (mv, self.perft_after(mv, depth - 1)))
+            .collect()
+    }
+
+    /// [`Board::perft_test`] with the root moves split between the threads of rayon's pool
+    #[cfg(feature = "rayon")]
+    pub fn perft_parallel(&self, depth: usize) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+
+        self.perft_divide_parallel(depth)
+            .into_iter()
+            .map(|(_, nodes)| nodes)
+            .sum()
+    }
+
+    /// The number of leaf nodes `depth` plies below `mv`, which must be legal
+    fn perft_after(&self, mv: ChessMove, depth: usize) -> usize {
+        let mut next_board = Board::standard();
+        unsafe { self.move_unchecked_into(mv, &mut next_board) };
+        next_board.perft_test(depth)
+    }
+}
@@ -0,0 +1,902 @@
+//! Reading and writing games in Portable Game Notation
+//!
+//! [`PgnReader`] reads one game at a time from any [`BufRead`], so even multi-gigabyte
+//! databases never have to fit in memory. Games are either collected into a [`Pgn`] tree, or
+//! handed to a [`Visitor`] which can skip the parts it doesn't need.
+
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead},
+    ops::ControlFlow,
+    str::FromStr,
+};
+
+use chess_bitboard::Color;
+
+use crate::{fen::ParseFenError, san::ParseSanError, Board, ChessMove};
+
+/// Movetext lines are wrapped to stay below 80 characters, as the export format requires
+const MAX_LINE_LEN: usize = 79;
+
+/// The tags every exported game has, in the order they must come in
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// The game is still going, or the result isn't known
+    Unknown,
+}
+
+impl GameResult {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        }
+    }
+
+    pub fn from_ascii_bytes(s: &[u8]) -> Option<Self> {
+        Some(match s {
+            b"1-0" => GameResult::WhiteWins,
+            b"0-1" => GameResult::BlackWins,
+            b"1/2-1/2" => GameResult::Draw,
+            b"*" => GameResult::Unknown,
+            _ => return None,
+        })
+    }
+
+    /// The result of a game won by `color`
+    pub fn win(color: Color) -> Self {
+        match color {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+}
+
+impl core::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_ascii_bytes(s.as_bytes()).ok_or(())
+    }
+}
+
+#[derive(Debug)]
+pub enum PgnError {
+    Io(io::Error),
+    Syntax {
+        line: usize,
+        message: &'static str,
+    },
+    InvalidFen {
+        line: usize,
+        error: ParseFenError,
+    },
+    IllegalMove {
+        line: usize,
+        san: String,
+        error: ParseSanError,
+    },
+}
+
+impl core::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::Io(error) => write!(f, "{error}"),
+            PgnError::Syntax { line, message } => write!(f, "{message} on line {line}"),
+            PgnError::InvalidFen { line, error } => {
+                write!(f, "Invalid FEN tag on line {line}: {error}")
+            }
+            PgnError::IllegalMove { line, san, error } => {
+                write!(f, "{error} {san} on line {line}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+impl From<io::Error> for PgnError {
+    fn from(error: io::Error) -> Self {
+        PgnError::Io(error)
+    }
+}
+
+/// A game, with its tags and the tree of moves and variations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pgn {
+    /// The tags in the order they were read, including the seven tag roster
+    pub tags: Vec<(String, String)>,
+    pub start: Board,
+    pub mainline: Variation,
+    pub result: GameResult,
+}
+
+/// A line of moves, either the mainline or an alternative to one of its moves
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Variation {
+    /// The comment before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<Move>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    /// The position the move is played in
+    pub board: Board,
+    pub mv: ChessMove,
+    /// Numeric annotation glyphs, where `!` is `1` and `?` is `2`
+    pub nags: Vec<u8>,
+    /// The comment after the move
+    pub comment: Option<String>,
+    /// Moves which could have been played instead of this one
+    pub variations: Vec<Variation>,
+}
+
+impl Move {
+    fn new(board: Board, mv: ChessMove) -> Self {
+        Self {
+            board,
+            mv,
+            nags: Vec::new(),
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+
+    /// The position after the move
+    pub fn board_after(&self) -> Board {
+        self.board
+            .move_new(self.mv)
+            .unwrap_or_else(|| panic!("{} is not a legal move in {}", self.mv, self.board))
+    }
+
+    pub fn san(&self) -> String {
+        self.board.san(self.mv)
+    }
+}
+
+impl Pgn {
+    /// A game without any moves or tags
+    pub fn new(start: Board) -> Self {
+        Self {
+            tags: Vec::new(),
+            start,
+            mainline: Variation::default(),
+            result: GameResult::Unknown,
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value,
+            None => self.tags.push((name.to_owned(), value)),
+        }
+    }
+
+    /// The position at the end of the mainline
+    pub fn end(&self) -> Board {
+        self.mainline
+            .moves
+            .last()
+            .map_or(self.start, Move::board_after)
+    }
+
+    /// Play `mv` at the end of the mainline, returns false if it isn't legal
+    #[must_use]
+    pub fn push(&mut self, mv: ChessMove) -> bool {
+        let board = self.end();
+        if !board.is_legal(mv) {
+            return false;
+        }
+
+        self.mainline.moves.push(Move::new(board, mv));
+        true
+    }
+}
+
+impl FromStr for Pgn {
+    type Err = PgnError;
+
+    /// Read the first game in `s`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut reader = PgnReader::new(s.as_bytes());
+        reader.read_pgn()?.ok_or(PgnError::Syntax {
+            line: reader.line,
+            message: "Missing game",
+        })
+    }
+}
+
+/// Receives the parts of a game as [`PgnReader::read_game`] reads them
+pub trait Visitor {
+    type Output;
+
+    fn begin_game(&mut self) {}
+
+    /// A tag pair like `[Event "F/S Return Match"]`, with the escapes already removed
+    fn tag(&mut self, _name: &str, _value: &str) {}
+
+    /// Called after the tags with the starting position, which is given by the `FEN` tag or is
+    /// the standard position. Return [`ControlFlow::Break`] to skip the moves
+    fn end_tags(&mut self, _start: &Board) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// A move in Standard Algebraic Notation. Annotations like `!?` are removed and passed to
+    /// [`Visitor::nag`] instead
+    fn san(&mut self, san: &str) -> Result<(), ParseSanError>;
+
+    fn nag(&mut self, _nag: u8) {}
+
+    fn comment(&mut self, _comment: &str) {}
+
+    /// Return [`ControlFlow::Break`] to skip the variation
+    fn begin_variation(&mut self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn end_variation(&mut self) {}
+
+    /// The result at the end of the movetext
+    fn result(&mut self, _result: GameResult) {}
+
+    fn end_game(&mut self) -> Self::Output;
+}
+
+/// Reads the games in a PGN database one at a time
+pub struct PgnReader<R> {
+    reader: R,
+    line: usize,
+    line_start: bool,
+    name: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 1,
+            line_start: true,
+            name: Vec::new(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Read the next game into a [`Pgn`], or `None` at the end of the database
+    pub fn read_pgn(&mut self) -> Result<Option<Pgn>, PgnError> {
+        self.read_game(&mut TreeBuilder::default())
+    }
+
+    /// Read the next game into `visitor`, or `None` at the end of the database.
+    ///
+    /// When the game can't be read the rest of it is skipped before returning the error, so the
+    /// next call reads the next game
+    pub fn read_game<V: Visitor>(
+        &mut self,
+        visitor: &mut V,
+    ) -> Result<Option<V::Output>, PgnError> {
+        self.skip_whitespace()?;
+        if self.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        visitor.begin_game();
+
+        let mut error = None;
+        let mut start = Board::standard();
+
+        loop {
+            self.skip_whitespace()?;
+            if self.peek()? != Some(b'[') {
+                break;
+            }
+
+            let line = self.line;
+            if let Err(message) = self.read_tag()? {
+                error.get_or_insert(PgnError::Syntax { line, message });
+                self.skip_line()?;
+                continue;
+            }
+
+            let name = String::from_utf8_lossy(&self.name);
+            let value = String::from_utf8_lossy(&self.buf);
+
+            if name == "FEN" {
+                match value.parse() {
+                    Ok(board) => start = board,
+                    Err(fen_error) => {
+                        error.get_or_insert(PgnError::InvalidFen {
+                            line,
+                            error: fen_error,
+                        });
+                    }
+                }
+            }
+
+            visitor.tag(&name, &value);
+        }
+
+        let mut skip = error.is_some() || visitor.end_tags(&start).is_break();
+        let mut depth = 0;
+        // the depth of the variation the visitor skipped
+        let mut skipped = None;
+
+        loop {
+            self.skip_whitespace()?;
+            let ignore = skip || skipped.is_some();
+            let line = self.line;
+
+            let Some(byte) = self.peek()? else { break };
+
+            match byte {
+                // the tags of the next game
+                b'[' => break,
+                b'{' => {
+                    self.bump()?;
+                    if !self.read_until(b'}')? {
+                        error.get_or_insert(PgnError::Syntax {
+                            line,
+                            message: "Unterminated comment",
+                        });
+                        break;
+                    }
+
+                    if !ignore {
+                        visitor.comment(String::from_utf8_lossy(&self.buf).trim());
+                    }
+                }
+                b';' => {
+                    self.bump()?;
+                    self.read_until(b'\n')?;
+                    if !ignore {
+                        visitor.comment(String::from_utf8_lossy(&self.buf).trim());
+                    }
+                }
+                b'(' => {
+                    self.bump()?;
+                    depth += 1;
+                    if !ignore && visitor.begin_variation().is_break() {
+                        skipped = Some(depth);
+                    }
+                }
+                b')' => {
+                    self.bump()?;
+                    if depth == 0 {
+                        error.get_or_insert(PgnError::Syntax {
+                            line,
+                            message: "Unmatched closing parenthesis",
+                        });
+                        skip = true;
+                        continue;
+                    }
+
+                    if skipped == Some(depth) {
+                        skipped = None;
+                    } else if !ignore {
+                        visitor.end_variation();
+                    }
+                    depth -= 1;
+                }
+                b'$' => {
+                    self.bump()?;
+                    self.read_symbol()?;
+                    let nag = std::str::from_utf8(&self.buf)
+                        .ok()
+                        .and_then(|nag| nag.parse().ok());
+
+                    match nag {
+                        Some(nag) if !ignore => visitor.nag(nag),
+                        Some(_) => (),
+                        None => {
+                            error.get_or_insert(PgnError::Syntax {
+                                line,
+                                message: "Invalid annotation glyph",
+                            });
+                            skip = true;
+                        }
+                    }
+                }
+                b'}' | b']' => {
+                    self.bump()?;
+                    error.get_or_insert(PgnError::Syntax {
+                        line,
+                        message: "Unexpected closing bracket",
+                    });
+                    skip = true;
+                }
+                _ => {
+                    self.read_symbol()?;
+                    let mut symbol = self.buf.as_slice();
+
+                    if let Some(result) = GameResult::from_ascii_bytes(symbol) {
+                        // a result inside a variation is ignored
+                        if depth == 0 {
+                            if !skip {
+                                visitor.result(result);
+                            }
+                            break;
+                        }
+                        continue;
+                    }
+
+                    // move numbers, which may be followed by the move like `1.e4`
+                    let digits = symbol.iter().take_while(|b| b.is_ascii_digit()).count();
+                    if digits > 0 && symbol.get(digits).is_none_or(|&b| b == b'.') {
+                        symbol = &symbol[digits..];
+                        while let [b'.', rest @ ..] = symbol {
+                            symbol = rest;
+                        }
+
+                        if symbol.is_empty() {
+                            continue;
+                        }
+                    }
+
+                    let annotation = symbol
+                        .iter()
+                        .rev()
+                        .take_while(|&&b| matches!(b, b'!' | b'?'))
+                        .count();
+                    let (san, annotation) = symbol.split_at(symbol.len() - annotation);
+
+                    if ignore {
+                        continue;
+                    }
+
+                    if !san.is_empty() {
+                        let result = match std::str::from_utf8(san) {
+                            Ok(san) => visitor.san(san),
+                            Err(_) => Err(ParseSanError::Invalid),
+                        };
+
+                        if let Err(san_error) = result {
+                            error.get_or_insert(PgnError::IllegalMove {
+                                line,
+                                san: String::from_utf8_lossy(san).into_owned(),
+                                error: san_error,
+                            });
+                            skip = true;
+                            continue;
+                        }
+                    }
+
+                    let nag = match annotation {
+                        b"" => None,
+                        b"!" => Some(1),
+                        b"?" => Some(2),
+                        b"!!" => Some(3),
+                        b"??" => Some(4),
+                        b"!?" => Some(5),
+                        b"?!" => Some(6),
+                        _ => None,
+                    };
+
+                    if let Some(nag) = nag {
+                        visitor.nag(nag);
+                    }
+                }
+            }
+        }
+
+        // variations which are still open at the end of the game are closed
+        if !skip {
+            let open = skipped.map_or(depth, |skipped| skipped - 1);
+            for _ in 0..open {
+                visitor.end_variation();
+            }
+        }
+
+        let output = visitor.end_game();
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(Some(output)),
+        }
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => return Ok(buf.first().copied()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn bump(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.peek()?;
+        if let Some(byte) = byte {
+            self.reader.consume(1);
+            self.line_start = byte == b'\n';
+            if byte == b'\n' {
+                self.line += 1;
+            }
+        }
+        Ok(byte)
+    }
+
+    /// Skips whitespace and lines escaped with `%`
+    fn skip_whitespace(&mut self) -> io::Result<()> {
+        while let Some(byte) = self.peek()? {
+            match byte {
+                b'%' if self.line_start => self.skip_line()?,
+                _ if byte.is_ascii_whitespace() => {
+                    self.bump()?;
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_line(&mut self) -> io::Result<()> {
+        while let Some(byte) = self.bump()? {
+            if byte == b'\n' {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads into `buf` up to and excluding `end`, returns false if it wasn't found
+    fn read_until(&mut self, end: u8) -> io::Result<bool> {
+        self.buf.clear();
+        while let Some(byte) = self.bump()? {
+            if byte == end {
+                return Ok(true);
+            }
+            self.buf.push(byte);
+        }
+        Ok(false)
+    }
+
+    /// Reads a move, move number, result or annotation into `buf`
+    fn read_symbol(&mut self) -> io::Result<()> {
+        self.buf.clear();
+        while let Some(byte) = self.peek()? {
+            if byte.is_ascii_whitespace() || b"{}()[];$".contains(&byte) {
+                break;
+            }
+            self.buf.push(byte);
+            self.bump()?;
+        }
+        Ok(())
+    }
+
+    /// Reads a tag pair into `name` and `buf`
+    fn read_tag(&mut self) -> io::Result<Result<(), &'static str>> {
+        self.bump()?;
+        self.skip_whitespace()?;
+
+        self.name.clear();
+        while let Some(byte) = self.peek()? {
+            if !(byte.is_ascii_alphanumeric() || byte == b'_') {
+                break;
+            }
+            self.name.push(byte);
+            self.bump()?;
+        }
+
+        if self.name.is_empty() {
+            return Ok(Err("Missing tag name"));
+        }
+
+        self.skip_whitespace()?;
+        if self.bump()? != Some(b'"') {
+            return Ok(Err("Missing tag value"));
+        }
+
+        self.buf.clear();
+        loop {
+            match self.bump()? {
+                Some(b'"') => break,
+                Some(b'\\') => match self.bump()? {
+                    Some(byte) => self.buf.push(byte),
+                    None => return Ok(Err("Unterminated tag value")),
+                },
+                Some(b'\n') | None => return Ok(Err("Unterminated tag value")),
+                Some(byte) => self.buf.push(byte),
+            }
+        }
+
+        self.skip_whitespace()?;
+        if self.bump()? != Some(b']') {
+            return Ok(Err("Missing closing bracket after tag"));
+        }
+
+        Ok(Ok(()))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<Pgn, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_pgn().transpose()
+    }
+}
+
+/// Collects a game into a [`Pgn`]
+struct TreeBuilder {
+    pgn: Pgn,
+    /// The variations being read, starting with the mainline, and the position at their end
+    lines: Vec<(Board, Variation)>,
+}
+
+impl Default for TreeBuilder {
+    fn default() -> Self {
+        Self {
+            pgn: Pgn::new(Board::standard()),
+            lines: Vec::new(),
+        }
+    }
+}
+
+impl TreeBuilder {
+    fn line(&mut self) -> &mut (Board, Variation) {
+        self.lines.last_mut().unwrap()
+    }
+}
+
+impl Visitor for TreeBuilder {
+    type Output = Pgn;
+
+    fn begin_game(&mut self) {
+        *self = Self::default();
+        self.lines.push((Board::standard(), Variation::default()));
+    }
+
+    fn tag(&mut self, name: &str, value: &str) {
+        if name == "Result" {
+            self.pgn.result = value.parse().unwrap_or(GameResult::Unknown);
+        }
+        self.pgn.tags.push((name.to_owned(), value.to_owned()));
+    }
+
+    fn end_tags(&mut self, start: &Board) -> ControlFlow<()> {
+        self.pgn.start = *start;
+        self.line().0 = *start;
+        ControlFlow::Continue(())
+    }
+
+    fn san(&mut self, san: &str) -> Result<(), ParseSanError> {
+        let (board, variation) = self.line();
+        let mv = board.parse_san(san)?;
+
+        variation.moves.push(Move::new(*board, mv));
+        assert!(board.move_mut(mv));
+        Ok(())
+    }
+
+    fn nag(&mut self, nag: u8) {
+        if let Some(last) = self.line().1.moves.last_mut() {
+            last.nags.push(nag);
+        }
+    }
+
+    fn comment(&mut self, comment: &str) {
+        let variation = &mut self.line().1;
+        let old = match variation.moves.last_mut() {
+            Some(last) => &mut last.comment,
+            None => &mut variation.comment,
+        };
+
+        match old {
+            Some(old) => {
+                old.push(' ');
+                old.push_str(comment);
+            }
+            None => *old = Some(comment.to_owned()),
+        }
+    }
+
+    fn begin_variation(&mut self) -> ControlFlow<()> {
+        // a variation replaces the last move, so there has to be one
+        let Some(last) = self.line().1.moves.last() else {
+            return ControlFlow::Break(());
+        };
+
+        let board = last.board;
+        self.lines.push((board, Variation::default()));
+        ControlFlow::Continue(())
+    }
+
+    fn end_variation(&mut self) {
+        let (_, variation) = self.lines.pop().unwrap();
+        let last = self.line().1.moves.last_mut().unwrap();
+        last.variations.push(variation);
+    }
+
+    fn result(&mut self, result: GameResult) {
+        self.pgn.result = result;
+    }
+
+    fn end_game(&mut self) -> Self::Output {
+        while self.lines.len() > 1 {
+            self.end_variation();
+        }
+
+        let mut pgn = core::mem::replace(&mut self.pgn, Pgn::new(Board::standard()));
+        if let Some((_, mainline)) = self.lines.pop() {
+            pgn.mainline = mainline;
+        }
+        pgn
+    }
+}
+
+/// Writes the game in the PGN export format: the seven tag roster followed by the other tags,
+/// then the movetext wrapped to lines below 80 characters. Games in a database should be
+/// separated by an empty line
+impl core::fmt::Display for Pgn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_tag(f: &mut std::fmt::Formatter<'_>, name: &str, value: &str) -> std::fmt::Result {
+            write!(f, "[{name} \"")?;
+            for c in value.chars() {
+                if c == '\\' || c == '"' {
+                    f.write_char('\\')?;
+                }
+                f.write_char(c)?;
+            }
+            writeln!(f, "\"]")
+        }
+
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.as_str(),
+                _ => self.tag(name).unwrap_or(default),
+            };
+            write_tag(f, name, value)?;
+        }
+
+        if self.start != Board::standard() {
+            write_tag(f, "SetUp", "1")?;
+            write_tag(f, "FEN", &self.start.to_string())?;
+        }
+
+        for (name, value) in &self.tags {
+            let written = SEVEN_TAG_ROSTER.iter().any(|(tag, _)| tag == name)
+                || name == "SetUp"
+                || name == "FEN";
+
+            if !written {
+                write_tag(f, name, value)?;
+            }
+        }
+
+        writeln!(f)?;
+
+        let mut movetext = Movetext::default();
+        movetext.variation(&self.mainline);
+        movetext.push(self.result.to_string());
+
+        let mut len = 0;
+        for token in movetext.tokens {
+            if len > 0 && len + 1 + token.len() > MAX_LINE_LEN {
+                writeln!(f)?;
+                len = 0;
+            }
+
+            if len > 0 {
+                f.write_char(' ')?;
+                len += 1;
+            }
+
+            f.write_str(&token)?;
+            len += token.len();
+        }
+
+        writeln!(f)
+    }
+}
+
+/// The tokens of the movetext, before they are wrapped into lines
+#[derive(Default)]
+struct Movetext {
+    tokens: Vec<String>,
+    /// Parentheses which open a variation are written before its first token
+    open: usize,
+}
+
+impl Movetext {
+    fn push(&mut self, token: String) {
+        let token = "(".repeat(self.open) + &token;
+        self.open = 0;
+        self.tokens.push(token);
+    }
+
+    fn close(&mut self) {
+        if self.open > 0 {
+            self.open -= 1;
+            self.push("()".to_owned());
+        } else {
+            self.tokens.last_mut().unwrap().push(')');
+        }
+    }
+
+    fn comment(&mut self, comment: &str) {
+        // a comment can't contain the brace that ends it
+        let words = comment
+            .split_whitespace()
+            .map(|word| word.replace('}', ""))
+            .collect::<Vec<_>>();
+
+        match words.as_slice() {
+            [] => self.push("{}".to_owned()),
+            [word] => self.push(format!("{{{word}}}")),
+            [first, middle @ .., last] => {
+                self.push(format!("{{{first}"));
+                for word in middle {
+                    self.push(word.clone());
+                }
+                self.push(format!("{last}}}"));
+            }
+        }
+    }
+
+    fn variation(&mut self, variation: &Variation) {
+        if let Some(comment) = &variation.comment {
+            self.comment(comment);
+        }
+
+        // black's moves are numbered when they don't directly follow white's move
+        let mut number = true;
+
+        for mv in &variation.moves {
+            let full_moves = mv.board.full_move_clock();
+            match mv.board.turn() {
+                Color::White => self.push(format!("{full_moves}.")),
+                Color::Black if number => self.push(format!("{full_moves}...")),
+                Color::Black => (),
+            }
+
+            self.push(mv.san());
+            for nag in &mv.nags {
+                self.push(format!("${nag}"));
+            }
+            number = false;
+
+            if let Some(comment) = &mv.comment {
+                self.comment(comment);
+                number = true;
+            }
+
+            for variation in &mv.variations {
+                self.open += 1;
+                self.variation(variation);
+                self.close();
+                number = true;
+            }
+        }
+    }
+}
@@ -187,6 +187,9 @@ impl RawBoard {
         // Piece::all().find(|&piece| self.pieces[piece].contains(pos))
     }
 
+    /// # Safety
+    ///
+    /// There must be a piece at `pos`
     #[inline]
     pub unsafe fn piece_of_unchecked(&self, pos: Pos) -> Piece {
         let pieces = self[Piece::Pawn] | self[Piece::Knight] | self[Piece::Bishop];
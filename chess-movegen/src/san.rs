@@ -0,0 +1,232 @@
+//! Standard Algebraic Notation, like `Nf3`, `exd5`, `O-O` or `e8=Q+`
+
+use std::fmt::Write;
+
+use chess_bitboard::{File, Piece, Pos, PromotionPiece, Rank, Side};
+
+use crate::{Board, ChessMove, MoveKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseSanError {
+    /// Not a move in algebraic notation
+    Invalid,
+    /// No legal move matches
+    IllegalMove,
+    /// More than one legal move matches
+    AmbiguousMove,
+}
+
+impl core::fmt::Display for ParseSanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseSanError::Invalid => write!(f, "Invalid move"),
+            ParseSanError::IllegalMove => write!(f, "Illegal move"),
+            ParseSanError::AmbiguousMove => {
+                write!(f, "Ambiguous move, more than one piece can make it")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseSanError {}
+
+fn piece_letter(piece: Piece) -> char {
+    match piece {
+        Piece::Pawn => 'P',
+        Piece::Knight => 'N',
+        Piece::Bishop => 'B',
+        Piece::Rook => 'R',
+        Piece::Queen => 'Q',
+        Piece::King => 'K',
+    }
+}
+
+/// A move in algebraic notation, which still has to be matched to a legal move
+#[derive(Debug)]
+enum San {
+    Castle(Side),
+    Normal {
+        piece: Piece,
+        file: Option<File>,
+        rank: Option<Rank>,
+        dest: Pos,
+        promotion: Option<PromotionPiece>,
+    },
+}
+
+impl San {
+    fn parse(s: &str) -> Result<Self, ParseSanError> {
+        let mut s = s.trim().as_bytes();
+        s = s.strip_suffix(b"e.p.").unwrap_or(s).trim_ascii_end();
+
+        // checks and annotations like `!?` aren't needed to find the move
+        while let [rest @ .., b'+' | b'#' | b'!' | b'?'] = s {
+            s = rest;
+        }
+
+        match s {
+            b"O-O" | b"0-0" | b"o-o" => return Ok(San::Castle(Side::King)),
+            b"O-O-O" | b"0-0-0" | b"o-o-o" => return Ok(San::Castle(Side::Queen)),
+            _ => (),
+        }
+
+        // piece letters are upper case, so `b` is always a file
+        let mut piece = Piece::Pawn;
+        if let [letter @ (b'P' | b'N' | b'B' | b'R' | b'Q' | b'K'), rest @ ..] = s {
+            piece = Piece::from_ascii_byte(*letter).unwrap();
+            s = rest;
+        }
+
+        // `e8=Q`, `e8Q` or `e8q`
+        let mut promotion = None;
+        if let [rest @ .., letter] = s {
+            let rest = rest.strip_suffix(b"=").unwrap_or(rest);
+
+            if let Some(piece) = PromotionPiece::from_ascii_byte(*letter) {
+                if rest.last().is_some_and(u8::is_ascii_digit) {
+                    promotion = Some(piece);
+                    s = rest;
+                }
+            }
+        }
+
+        let [rest @ .., file, rank] = s else {
+            return Err(ParseSanError::Invalid);
+        };
+        let dest = Pos::from_ascii_bytes(&[*file, *rank]).ok_or(ParseSanError::Invalid)?;
+        s = rest;
+
+        // the capture marker is optional
+        if let [rest @ .., b'x' | b':' | b'-'] = s {
+            s = rest;
+        }
+
+        let (file, rank) = match *s {
+            [] => (None, None),
+            [rank @ b'1'..=b'8'] => (None, Rank::from_ascii_byte(rank)),
+            [file @ b'a'..=b'h'] => (File::from_ascii_byte(file), None),
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                (File::from_ascii_byte(file), Rank::from_ascii_byte(rank))
+            }
+            _ => return Err(ParseSanError::Invalid),
+        };
+
+        Ok(San::Normal {
+            piece,
+            file,
+            rank,
+            dest,
+            promotion,
+        })
+    }
+
+    fn matches(&self, board: &Board, mv: ChessMove) -> bool {
+        match *self {
+            San::Castle(side) => board.move_kind(mv) == MoveKind::Castle(side),
+            San::Normal {
+                piece,
+                file,
+                rank,
+                dest,
+                promotion,
+            } => {
+                mv.dest == dest
+                    && mv.piece == promotion
+                    && board.raw.piece_of(mv.source) == Some(piece)
+                    && file.is_none_or(|file| file == mv.source.file())
+                    && rank.is_none_or(|rank| rank == mv.source.rank())
+                    && !board.move_kind(mv).is_castle()
+            }
+        }
+    }
+}
+
+impl Board {
+    /// `mv` in Standard Algebraic Notation, with only as much of the starting square as is
+    /// needed to tell it apart from other moves, and `+` or `#` if it checks or mates.
+    ///
+    /// # Panics
+    ///
+    /// If `mv` isn't a legal move
+    pub fn san(&self, mv: ChessMove) -> String {
+        let new = self
+            .move_new(mv)
+            .unwrap_or_else(|| panic!("{mv} is not a legal move in {self}"));
+
+        let mut san = String::new();
+        let kind = self.move_kind(mv);
+        let piece = self.raw.piece_of(mv.source).unwrap();
+
+        match kind {
+            MoveKind::Castle(Side::King) => san.push_str("O-O"),
+            MoveKind::Castle(Side::Queen) => san.push_str("O-O-O"),
+            _ if piece == Piece::Pawn => {
+                if kind.is_capture() {
+                    write!(san, "{}x", mv.source.file()).unwrap();
+                }
+
+                write!(san, "{}", mv.dest).unwrap();
+
+                if let Some(promotion) = mv.piece {
+                    write!(san, "={promotion}").unwrap();
+                }
+            }
+            _ => {
+                san.push(piece_letter(piece));
+
+                let others = self.legals().filter(|other| {
+                    other.dest == mv.dest
+                        && other.source != mv.source
+                        && self.raw.piece_of(other.source) == Some(piece)
+                });
+
+                let (mut ambiguous, mut same_file, mut same_rank) = (false, false, false);
+                for other in others {
+                    ambiguous = true;
+                    same_file |= other.source.file() == mv.source.file();
+                    same_rank |= other.source.rank() == mv.source.rank();
+                }
+
+                if ambiguous {
+                    if !same_file {
+                        write!(san, "{}", mv.source.file()).unwrap();
+                    } else if !same_rank {
+                        write!(san, "{}", mv.source.rank()).unwrap();
+                    } else {
+                        write!(san, "{}", mv.source).unwrap();
+                    }
+                }
+
+                if kind.is_capture() {
+                    san.push('x');
+                }
+
+                write!(san, "{}", mv.dest).unwrap();
+            }
+        }
+
+        if new.in_check() {
+            san.push(if new.legals().is_empty() { '#' } else { '+' });
+        }
+
+        san
+    }
+
+    /// Find the legal move written as `san` in algebraic notation.
+    ///
+    /// This is lenient: the capture marker, checks and annotations like `!?` are optional and
+    /// not validated, castling may use zeros, promotions may leave out the `=`, and the starting
+    /// square may be given even when it isn't needed
+    pub fn parse_san(&self, san: &str) -> Result<ChessMove, ParseSanError> {
+        let san = San::parse(san)?;
+
+        let mut moves = self.legals().filter(|&mv| san.matches(self, mv));
+        let mv = moves.next().ok_or(ParseSanError::IllegalMove)?;
+
+        if moves.next().is_some() {
+            return Err(ParseSanError::AmbiguousMove);
+        }
+
+        Ok(mv)
+    }
+}
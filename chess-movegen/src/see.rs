@@ -0,0 +1,113 @@
+use chess_bitboard::{BitBoard, Piece, Pos};
+
+use crate::{Board, ChessMove};
+
+/// The value of each piece in [`Board::see`], in centipawns.
+///
+/// Knights and bishops are worth the same, so trading one for the other is even. The king is
+/// never captured, so its value doesn't matter
+pub const SEE_VALUES: [i32; 6] = [100, 300, 300, 500, 900, 0];
+
+impl Board {
+    /// Static exchange evaluation: the material the side to move wins or loses in centipawns
+    /// if both sides keep capturing on `mv.dest` with their least valuable piece, and either
+    /// side may stop capturing once that's better for them.
+    ///
+    /// Pieces behind other pieces join in as the ones in front of them capture (x-rays). Pins
+    /// are ignored, and kings only capture when the other side can't recapture. A quiet move
+    /// scores the piece it loses if it can be captured, and castling always scores 0
+    ///
+    /// `mv` must be a legal move
+    pub fn see(&self, mv: ChessMove) -> i32 {
+        let Some(piece) = self.raw.piece_of(mv.source) else {
+            return 0;
+        };
+
+        if self.castle_side(mv).is_some() {
+            return 0;
+        }
+
+        let target = mv.dest;
+        let mut occupied = self.raw.all() ^ BitBoard::from(mv.source);
+
+        // the material won by each capture in the sequence, from the point of view of the
+        // side making it, assuming the other side recaptures
+        let mut gains = arrayvec::ArrayVec::<i32, 32>::new();
+
+        let mut gain = match self.raw.piece_of(target) {
+            Some(captured) => SEE_VALUES[captured],
+            None if piece == Piece::Pawn && Some(target) == self.enpassant_pos() => {
+                let pawn = Pos::new(target.file(), self.turn.enpassant_pawn_rank());
+                occupied ^= BitBoard::from(pawn);
+                SEE_VALUES[Piece::Pawn]
+            }
+            None => 0,
+        };
+
+        // the value of the piece on the target square, which the next capture wins
+        let mut at_risk = SEE_VALUES[piece];
+        if let Some(promotion) = mv.piece {
+            let promotion = SEE_VALUES[promotion.to_piece()];
+            gain += promotion - SEE_VALUES[Piece::Pawn];
+            at_risk = promotion;
+        }
+        gains.push(gain);
+
+        let bishops = self.raw[Piece::Bishop] | self.raw[Piece::Queen];
+        let rooks = self.raw[Piece::Rook] | self.raw[Piece::Queen];
+
+        let mut attackers = self.raw.attackers_to(target, occupied) & occupied;
+        let mut side = !self.turn;
+
+        while !gains.is_full() {
+            let Some((piece, pos)) = self.least_valuable(attackers & self.raw[side]) else {
+                break;
+            };
+
+            occupied ^= BitBoard::from(pos);
+            attackers &= occupied;
+
+            // moving a piece off the line to the target reveals the pieces behind it
+            if matches!(piece, Piece::Pawn | Piece::Bishop | Piece::Queen) {
+                attackers |= chess_lookup::bishop_moves(target, occupied) & bishops & occupied;
+            }
+            if matches!(piece, Piece::Rook | Piece::Queen) {
+                attackers |= chess_lookup::rook_moves(target, occupied) & rooks & occupied;
+            }
+
+            if piece == Piece::King && (attackers & self.raw[!side]).any() {
+                break;
+            }
+
+            let mut gain = at_risk - gains[gains.len() - 1];
+            at_risk = SEE_VALUES[piece];
+            if piece == Piece::Pawn && target.rank() == chess_lookup::PROMOTION_RANK[side] {
+                gain += SEE_VALUES[Piece::Queen] - SEE_VALUES[Piece::Pawn];
+                at_risk = SEE_VALUES[Piece::Queen];
+            }
+            gains.push(gain);
+
+            side = !side;
+        }
+
+        // each side only captures if that's better than stopping
+        for i in (1..gains.len()).rev() {
+            gains[i - 1] = -(-gains[i - 1]).max(gains[i]);
+        }
+
+        gains[0]
+    }
+
+    /// Whether [`Board::see`] of `mv` is at least `threshold`
+    #[inline]
+    pub fn see_ge(&self, mv: ChessMove, threshold: i32) -> bool {
+        self.see(mv) >= threshold
+    }
+
+    fn least_valuable(&self, attackers: BitBoard) -> Option<(Piece, Pos)> {
+        Piece::all().find_map(|piece| {
+            let pos = (attackers & self.raw[piece]).iter().next()?;
+            Some((piece, pos))
+        })
+    }
+}
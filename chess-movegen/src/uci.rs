@@ -0,0 +1,91 @@
+//! Long algebraic notation as used by UCI, like `e2e4`, `e7e8q` or `0000` for the null move
+
+use chess_bitboard::{Piece, Side};
+
+use crate::{Board, ChessMove, MoveKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseUciMoveError {
+    /// Not a move in long algebraic notation
+    Invalid,
+    /// No legal move matches
+    IllegalMove,
+}
+
+impl core::fmt::Display for ParseUciMoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseUciMoveError::Invalid => write!(f, "Invalid move"),
+            ParseUciMoveError::IllegalMove => write!(f, "Illegal move"),
+        }
+    }
+}
+
+impl std::error::Error for ParseUciMoveError {}
+
+/// Formats a move in long algebraic notation, see [`ChessMove::uci`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UciMove(pub ChessMove);
+
+impl core::fmt::Display for UciMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mv = self.0;
+        if mv == ChessMove::NULL {
+            return write!(f, "0000");
+        }
+
+        write!(f, "{}{}", mv.source, mv.dest)?;
+        if let Some(promotion) = mv.piece {
+            write!(f, "{}", promotion.to_string().to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+impl ChessMove {
+    /// Formats the move in long algebraic notation, e.g. `e2e4`, `e7e8q` or `0000`
+    #[inline]
+    pub fn uci(self) -> UciMove {
+        UciMove(self)
+    }
+}
+
+impl Board {
+    /// Find the legal move written as `s` in long algebraic notation.
+    ///
+    /// Castling may be written as the king moving two squares, like `e1g1`, or as the king
+    /// capturing its own rook, like `e1h1`
+    pub fn parse_uci_move(&self, s: &str) -> Result<ChessMove, ParseUciMoveError> {
+        let mv =
+            ChessMove::from_ascii_bytes(s.trim().as_bytes()).ok_or(ParseUciMoveError::Invalid)?;
+
+        let king_takes_rook = mv.piece.is_none()
+            && self.raw.piece_of(mv.source) == Some(Piece::King)
+            && self.raw.piece_of(mv.dest) == Some(Piece::Rook)
+            && self.raw[self.turn].contains(mv.dest);
+
+        if king_takes_rook {
+            // in Chess960 the king may start on either side of the board's center
+            let side = if mv.dest.file() > mv.source.file() {
+                Side::King
+            } else {
+                Side::Queen
+            };
+            return self
+                .legals()
+                .find(|&other| {
+                    // the king castles with the rook it takes, not some other rook on that side
+                    self.castle_rights.rook_file(side, self.turn) == mv.dest.file()
+                        && other.source == mv.source
+                        && self.move_kind(other) == MoveKind::Castle(side)
+                })
+                .ok_or(ParseUciMoveError::IllegalMove);
+        }
+
+        if self.is_legal(mv) {
+            Ok(mv)
+        } else {
+            Err(ParseUciMoveError::IllegalMove)
+        }
+    }
+}
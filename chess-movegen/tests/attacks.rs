@@ -0,0 +1,90 @@
+use chess_bitboard::{BitBoard, Color, Pos};
+use chess_movegen::Board;
+
+fn board(fen: &str) -> Board {
+    fen.parse().unwrap()
+}
+
+#[test]
+fn attackers_to() {
+    let board = board("4k3/8/8/3p4/4P3/2N5/8/3RK3 w - - 0 1");
+
+    assert_eq!(
+        board.attackers_to(Pos::D5, Color::White),
+        BitBoard::from_iter([Pos::E4, Pos::C3, Pos::D1])
+    );
+    assert_eq!(
+        board.attackers_to(Pos::E4, Color::Black),
+        BitBoard::from(Pos::D5)
+    );
+    assert!(board.is_attacked(Pos::D5, Color::White));
+    assert!(!board.is_attacked(Pos::D4, Color::Black));
+}
+
+#[test]
+fn attacked_squares() {
+    let board = board("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+
+    assert_eq!(
+        board.attacked_squares(Color::White),
+        BitBoard::from_iter([
+            Pos::B3,
+            Pos::C2,
+            Pos::D1,
+            Pos::D2,
+            Pos::E2,
+            Pos::F2,
+            Pos::F1
+        ])
+    );
+}
+
+#[test]
+fn xray_attackers() {
+    // the queen is behind the rook
+    let board = board("4k3/8/8/3p4/8/8/3R4/3QK3 w - - 0 1");
+    assert_eq!(
+        board.attackers_to(Pos::D5, Color::White),
+        BitBoard::from(Pos::D2)
+    );
+    assert_eq!(
+        board.xray_attackers(Pos::D5, Color::White),
+        BitBoard::from(Pos::D1)
+    );
+    assert_eq!(
+        board.xray_attackers(Pos::E1, Color::Black),
+        BitBoard::empty()
+    );
+}
+
+#[test]
+fn checkers_and_pins() {
+    let board = board("4k3/8/8/8/1b6/8/3N4/4K2r w - - 0 1");
+    assert_eq!(board.checkers(), BitBoard::from(Pos::H1));
+    assert_eq!(board.pinned(), BitBoard::from(Pos::D2));
+
+    // an enemy piece in the way doesn't pin anything
+    let board = self::board("4k3/8/8/8/1b6/8/3n4/4K3 w - - 0 1");
+    assert_eq!(board.checkers(), BitBoard::empty());
+    assert_eq!(board.pinned(), BitBoard::empty());
+}
+
+#[test]
+fn checkers_match_attackers_to_king() {
+    fn check(board: &Board, depth: usize) {
+        let king = board.king_sq(board.turn());
+        assert_eq!(board.checkers(), board.attackers_to(king, !board.turn()));
+        assert_eq!(board.in_check(), board.is_attacked(king, !board.turn()));
+
+        if depth > 0 {
+            for mv in board.legals() {
+                check(&board.move_new(mv).unwrap(), depth - 1);
+            }
+        }
+    }
+
+    check(
+        &board("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+        2,
+    );
+}
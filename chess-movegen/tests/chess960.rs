@@ -0,0 +1,105 @@
+use chess_bitboard::Side;
+use chess_movegen::{fen::ParseFenError, Board, BoardValidationError, MoveKind};
+
+fn castles(board: &Board) -> Vec<String> {
+    board
+        .legals()
+        .filter(|&mv| board.move_kind(mv).is_castle())
+        .map(|mv| mv.uci().to_string())
+        .collect()
+}
+
+#[test]
+fn start_positions() {
+    assert_eq!(Board::chess960(518), Some(Board::standard()));
+    assert_eq!(Board::chess960(960), None);
+
+    let first = Board::chess960(0).unwrap();
+    assert_eq!(
+        first.to_string(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+    );
+    assert_eq!(
+        first.shredder_fen().to_string(),
+        "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w HFhf - 0 1"
+    );
+    assert_eq!(
+        Board::chess960(959).unwrap().to_string(),
+        "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+    );
+}
+
+#[test]
+fn parse_and_write_castle_rights() {
+    let shredder: Board = "rk5r/8/8/8/8/8/8/RK5R w HAha - 0 1".parse().unwrap();
+    let xfen: Board = "rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1".parse().unwrap();
+    assert_eq!(shredder, xfen);
+    assert_eq!(shredder.to_string(), "rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1");
+
+    // X-FEN writes the file when another rook is further out on that side
+    let inner: Board = "4k3/8/8/8/8/8/8/RR2K2R w KB - 0 1".parse().unwrap();
+    assert_eq!(inner.to_string(), "4k3/8/8/8/8/8/8/RR2K2R w KB - 0 1");
+    assert_eq!(
+        inner.shredder_fen().to_string(),
+        "4k3/8/8/8/8/8/8/RR2K2R w HB - 0 1"
+    );
+    assert_eq!(castles(&inner), ["e1c1", "e1g1"]);
+
+    let invalid = [
+        // no rook on that file
+        "4k3/8/8/8/8/8/8/R3K2R w G - 0 1",
+        // no rook towards that side
+        "4k3/8/8/8/8/8/8/4K2R w Q - 0 1",
+        // the king isn't on the back rank
+        "4k3/8/8/8/8/8/4K3/R6R w A - 0 1",
+    ];
+    for fen in invalid {
+        assert_eq!(
+            fen.parse::<Board>(),
+            Err(ParseFenError::BoardValidation(
+                BoardValidationError::InvalidCastleRights
+            )),
+            "{fen}"
+        );
+    }
+}
+
+#[test]
+fn castling() {
+    // without the king on the e-file, castling is written as the king moving onto the rook
+    let board: Board = "rk5r/8/8/8/8/8/8/RK5R w KQkq - 0 1".parse().unwrap();
+    assert_eq!(castles(&board), ["b1a1", "b1h1"]);
+
+    let kingside = board.parse_uci_move("b1h1").unwrap();
+    assert_eq!(board.move_kind(kingside), MoveKind::Castle(Side::King));
+    assert_eq!(board.parse_san("O-O"), Ok(kingside));
+    assert_eq!(
+        board.move_new(kingside).unwrap().to_string(),
+        "rk5r/8/8/8/8/8/8/R4RK1 b kq - 1 1"
+    );
+
+    let queenside = board.parse_san("O-O-O").unwrap();
+    assert_eq!(
+        board.move_new(queenside).unwrap().to_string(),
+        "rk5r/8/8/8/8/8/8/2KR3R b kq - 1 1"
+    );
+
+    // the king stays on g1, and the rook checks from f1
+    let board: Board = "5k2/8/8/8/8/8/8/6KR w K - 0 1".parse().unwrap();
+    let mv = board.parse_san("O-O+").unwrap();
+    assert_eq!(mv.uci().to_string(), "g1h1");
+    assert!(board.gives_check(mv));
+    assert_eq!(board.see(mv), 0);
+
+    // the rook on b1 blocks the rook on a1 from attacking c1 until it moves to d1
+    let board: Board = "4k3/8/8/8/8/8/8/1RK5 w Q - 0 1".parse().unwrap();
+    assert_eq!(castles(&board), ["c1b1"]);
+    let board: Board = "4k3/8/8/8/8/8/8/rRK5 w Q - 0 1".parse().unwrap();
+    assert!(castles(&board).is_empty());
+
+    // every square between the king and rook and their destinations has to be empty
+    let board: Board = "4k3/8/8/8/8/8/8/1K1N2R1 w G - 0 1".parse().unwrap();
+    assert!(castles(&board).is_empty());
+    let board: Board = "4k3/8/8/8/8/8/8/RN2K3 w A - 0 1".parse().unwrap();
+    assert!(castles(&board).is_empty());
+}
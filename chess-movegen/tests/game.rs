@@ -0,0 +1,140 @@
+use chess_bitboard::Color;
+use chess_movegen::{pgn::GameResult, Board, DrawReason, Game, Outcome};
+
+fn game(fen: &str) -> Game {
+    Game::new(fen.parse().unwrap())
+}
+
+fn play(game: &mut Game, moves: &str) {
+    for mv in moves.split_whitespace() {
+        let mv = game.board().parse_uci_move(mv).unwrap();
+        assert!(game.push(mv), "{mv} in {}", game.board());
+    }
+}
+
+#[test]
+fn push_and_pop() {
+    let mut game = Game::default();
+    play(&mut game, "e2e4 e7e5 g1f3");
+
+    assert_eq!(game.moves().len(), 3);
+    assert_eq!(game.boards().len(), 4);
+    assert_eq!(game.board().turn(), Color::Black);
+    assert!(!game.push("e2e4".parse().unwrap()));
+
+    assert_eq!(game.pop(), Some("g1f3".parse().unwrap()));
+    assert_eq!(game.pop(), Some("e7e5".parse().unwrap()));
+    assert_eq!(game.pop(), Some("e2e4".parse().unwrap()));
+    assert_eq!(game.pop(), None);
+    assert_eq!(game.board(), &Board::standard());
+}
+
+#[test]
+fn repetitions() {
+    let mut game = Game::default();
+    let shuffle = "g1f3 g8f6 f3g1 f6g8";
+
+    play(&mut game, shuffle);
+    assert_eq!(game.repetitions(), 2);
+    play(&mut game, shuffle);
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(
+        game.outcome(),
+        Some(Outcome::Draw(DrawReason::ThreefoldRepetition))
+    );
+
+    // undoing a move also undoes the repetition
+    game.pop();
+    assert_eq!(game.outcome(), None);
+    play(&mut game, "f6g8");
+
+    play(&mut game, shuffle);
+    play(&mut game, shuffle);
+    assert_eq!(game.repetitions(), 5);
+    assert_eq!(
+        game.outcome(),
+        Some(Outcome::Draw(DrawReason::FivefoldRepetition))
+    );
+}
+
+#[test]
+fn irreversible_moves_reset_repetitions() {
+    let mut game = Game::default();
+    play(&mut game, "g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(game.repetitions(), 2);
+
+    // the knights are back on their squares, but the pawns moved in between, so this only
+    // repeats the position after e7e5, whose en passant square nothing can capture on
+    play(&mut game, "e2e4 e7e5 g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(game.repetitions(), 2);
+    play(&mut game, "g1f3 g8f6 f3g1 f6g8");
+    assert_eq!(game.repetitions(), 3);
+
+    // the en passant square after a double push doesn't count when nothing can capture there
+    let mut game = Game::default();
+    play(&mut game, "e2e4 g8f6 g1f3 f6g8 f3g1 g8f6 g1f3 f6g8 f3g1");
+    assert_eq!(game.repetitions(), 3);
+
+    // but it does when a pawn can capture en passant
+    let mut game = self::game("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1");
+    play(&mut game, "e2e4 e8d8 e1d1 d8e8 d1e1");
+    assert_eq!(game.repetitions(), 1);
+    play(&mut game, "e8d8 e1d1 d8e8 d1e1");
+    assert_eq!(game.repetitions(), 2);
+
+    // losing the right to castle makes the position different
+    let mut game = self::game("r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1");
+    play(&mut game, "h1h2 a8a7 h2h1 a7a8");
+    assert_eq!(game.repetitions(), 1);
+    play(&mut game, "h1h2 a8a7 h2h1 a7a8");
+    assert_eq!(game.repetitions(), 2);
+}
+
+#[test]
+fn outcomes() {
+    let mut mate = Game::default();
+    play(&mut mate, "f2f3 e7e5 g2g4 d8h4");
+    assert_eq!(
+        mate.outcome(),
+        Some(Outcome::Checkmate {
+            winner: Color::Black
+        })
+    );
+    assert_eq!(mate.to_pgn().result, GameResult::BlackWins);
+
+    let draw = |fen: &str| game(fen).outcome();
+    assert_eq!(
+        draw("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"),
+        Some(Outcome::Draw(DrawReason::Stalemate))
+    );
+    assert_eq!(
+        draw("8/8/4k3/8/8/3NK3/8/8 w - - 0 1"),
+        Some(Outcome::Draw(DrawReason::InsufficientMaterial))
+    );
+    // bishops which can only ever be on dark squares
+    assert_eq!(
+        draw("8/8/2b1k3/8/8/3BK3/8/8 w - - 0 1"),
+        Some(Outcome::Draw(DrawReason::DeadPosition))
+    );
+    assert_eq!(draw("8/8/3bk3/8/8/3BK3/8/8 w - - 0 1"), None);
+    assert_eq!(
+        draw("4k3/8/8/8/8/8/8/R3K3 w - - 100 80"),
+        Some(Outcome::Draw(DrawReason::FiftyMoves))
+    );
+    assert_eq!(
+        draw("4k3/8/8/8/8/8/8/R3K3 w - - 150 80"),
+        Some(Outcome::Draw(DrawReason::SeventyFiveMoves))
+    );
+    assert_eq!(draw("4k3/8/8/8/8/8/8/R3K3 w - - 99 80"), None);
+
+    // checkmate takes precedence over the fifty move rule
+    assert_eq!(
+        draw("R6k/8/6K1/8/8/8/8/8 b - - 100 80"),
+        Some(Outcome::Checkmate {
+            winner: Color::White
+        })
+    );
+
+    assert!(DrawReason::ThreefoldRepetition.is_claimed());
+    assert!(!DrawReason::FivefoldRepetition.is_claimed());
+}
@@ -0,0 +1,95 @@
+use chess_bitboard::{Piece, PromotionPiece, Side};
+use chess_movegen::{Board, ChessMove, MoveKind};
+
+fn kind(fen: &str, mv: &str, promotion: Option<PromotionPiece>) -> MoveKind {
+    let board: Board = fen.parse().unwrap();
+    let mv = ChessMove {
+        piece: promotion,
+        ..mv.parse().unwrap()
+    };
+    assert!(board.is_legal(mv), "{mv} is illegal in {fen}");
+    board.move_kind(mv)
+}
+
+#[test]
+fn move_kinds() {
+    let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    assert_eq!(kind(kiwipete, "a2a3", None), MoveKind::Quiet);
+    assert_eq!(kind(kiwipete, "e5f7", None), MoveKind::Capture);
+    assert_eq!(kind(kiwipete, "e1g1", None), MoveKind::Castle(Side::King));
+    assert_eq!(kind(kiwipete, "e1c1", None), MoveKind::Castle(Side::Queen));
+    // the king moving one square isn't castling
+    assert_eq!(kind(kiwipete, "e1f1", None), MoveKind::Quiet);
+
+    let fen = "1r2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(kind(fen, "e5d6", None), MoveKind::EnPassant);
+    assert_eq!(kind(fen, "e5e6", None), MoveKind::Quiet);
+
+    let queen = Some(PromotionPiece::Queen);
+    assert_eq!(
+        kind(fen, "a7a8", queen),
+        MoveKind::Promotion(PromotionPiece::Queen)
+    );
+    assert_eq!(
+        kind(fen, "a7b8", Some(PromotionPiece::Knight)),
+        MoveKind::PromotionCapture(PromotionPiece::Knight)
+    );
+
+    let board: Board = fen.parse().unwrap();
+    let ep: ChessMove = "e5d6".parse().unwrap();
+    assert!(board.is_capture(ep));
+    assert_eq!(board.captured_piece(ep), Some(Piece::Pawn));
+}
+
+#[test]
+fn gives_check() {
+    let check = |fen: &str, mv: &str| {
+        let board: Board = fen.parse().unwrap();
+        board.gives_check(mv.parse().unwrap())
+    };
+
+    // direct and discovered checks
+    assert!(check("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"));
+    assert!(check("4k3/8/8/8/8/8/4N3/4RK2 w - - 0 1", "e2c3"));
+    assert!(!check("4k3/8/8/8/8/8/4N3/4RK2 w - - 0 1", "f1f2"));
+    // the rook checks after castling
+    assert!(check("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1"));
+    // capturing en passant uncovers the rook
+    assert!(check("8/8/8/k2pP2R/8/8/8/4K3 w - d6 0 1", "e5d6"));
+}
+
+/// `gives_check` and `is_capture` agree with making the move for every move in a few plies
+#[test]
+fn move_kinds_match_making_moves() {
+    fn check(board: &Board, depth: usize) {
+        for mv in board.legals() {
+            let new = board.move_new(mv).unwrap();
+            let kind = board.move_kind(mv);
+
+            assert_eq!(board.gives_check(mv), new.in_check(), "{mv} in {board}");
+
+            let enemies = board.raw()[!board.turn()].count();
+            let captured = enemies != new.raw()[!board.turn()].count();
+            assert_eq!(kind.is_capture(), captured, "{mv} in {board}");
+            assert_eq!(kind.is_promotion(), mv.piece.is_some());
+            assert_eq!(
+                kind.is_castle(),
+                (new.raw()[board.turn()] & board.raw()[board.turn()]).count() + 2
+                    == board.raw()[board.turn()].count(),
+                "{mv} in {board}"
+            );
+
+            if depth > 0 {
+                check(&new, depth - 1);
+            }
+        }
+    }
+
+    for fen in [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ] {
+        check(&fen.parse().unwrap(), 2);
+    }
+}
@@ -1,4 +1,4 @@
-use chess_movegen::Board;
+use chess_movegen::{Board, PerftStats, PerftTable};
 
 fn movegen_perft_test(fen: &str, depth: usize, result: usize) {
     let board: Board = fen.parse().unwrap();
@@ -171,3 +171,153 @@ fn movegen_issue_15() {
         .unwrap();
     let _ = board.legals();
 }
+
+#[test]
+fn movegen_perft_en_passant_file_pin() {
+    movegen_perft_test("4r2k/8/8/3Pp3/8/8/8/4K3 w - e6 0 1", 5, 60686);
+}
+
+#[test]
+fn movegen_perft_en_passant_along_pin() {
+    movegen_perft_test("7k/5b2/8/3Pp3/8/1K6/8/8 w - e6 0 1", 5, 49222);
+}
+
+#[test]
+fn movegen_perft_en_passant_rank_pin() {
+    movegen_perft_test("8/8/8/K1pP3r/8/8/8/7k w - c6 0 1", 4, 7046);
+}
+
+#[test]
+fn movegen_perft_chess960_1() {
+    movegen_perft_test(
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        5,
+        8146062,
+    );
+}
+
+#[test]
+fn movegen_perft_chess960_2() {
+    movegen_perft_test(
+        "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        5,
+        16253601,
+    );
+}
+
+#[test]
+fn movegen_perft_chess960_3() {
+    movegen_perft_test(
+        "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        5,
+        6417013,
+    );
+}
+
+#[test]
+fn movegen_perft_chess960_4() {
+    movegen_perft_test(
+        "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        5,
+        9183776,
+    );
+}
+
+#[test]
+fn movegen_perft_chess960_5() {
+    movegen_perft_test(
+        "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        5,
+        34030312,
+    );
+}
+
+#[test]
+fn movegen_perft_chess960_start() {
+    for index in [0, 959] {
+        assert_eq!(Board::chess960(index).unwrap().perft_test(4), 201143);
+    }
+}
+
+#[test]
+fn movegen_perft_divide() {
+    let divide = Board::standard().perft_divide(3);
+    assert_eq!(divide.len(), 20);
+    assert_eq!(divide.iter().map(|&(_, nodes)| nodes).sum::<usize>(), 8902);
+
+    let e2e4 = divide.iter().find(|(mv, _)| mv.uci().to_string() == "e2e4");
+    assert_eq!(e2e4.map(|&(_, nodes)| nodes), Some(600));
+
+    assert_eq!(Board::standard().perft_divide(0), []);
+    assert_eq!(Board::standard().perft_test(0), 1);
+}
+
+#[test]
+fn movegen_perft_stats() {
+    let stats = |fen: &str, depth| fen.parse::<Board>().unwrap().perft_stats(depth);
+
+    assert_eq!(
+        Board::standard().perft_stats(4),
+        PerftStats {
+            nodes: 197281,
+            captures: 1576,
+            en_passants: 0,
+            castles: 0,
+            promotions: 0,
+            checks: 469,
+            checkmates: 8,
+        }
+    );
+    assert_eq!(
+        stats(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            3
+        ),
+        PerftStats {
+            nodes: 97862,
+            captures: 17102,
+            en_passants: 45,
+            castles: 3162,
+            promotions: 0,
+            checks: 993,
+            checkmates: 1,
+        }
+    );
+    assert_eq!(
+        stats("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4),
+        PerftStats {
+            nodes: 43238,
+            captures: 3348,
+            en_passants: 123,
+            castles: 0,
+            promotions: 0,
+            checks: 1680,
+            checkmates: 17,
+        }
+    );
+}
+
+#[test]
+fn movegen_perft_hashed() {
+    let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+        .parse()
+        .unwrap();
+
+    // a tiny table replaces entries all the time, which must not change the count
+    for size_mb in [0, 16] {
+        let mut table = PerftTable::new(size_mb);
+        assert_eq!(board.perft_hashed(4, &mut table), 4085603);
+        assert_eq!(board.perft_hashed(4, &mut table), 4085603);
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn movegen_perft_parallel() {
+    let board = Board::standard();
+    assert_eq!(board.perft_parallel(4), 197281);
+
+    assert_eq!(board.perft_divide_parallel(3), board.perft_divide(3));
+    assert_eq!(board.perft_divide_parallel(0), []);
+    assert_eq!(board.perft_parallel(0), 1);
+}
@@ -0,0 +1,197 @@
+use std::ops::ControlFlow;
+
+use chess_movegen::{
+    pgn::{GameResult, Pgn, PgnError, PgnReader, Visitor},
+    san::ParseSanError,
+    Board,
+};
+
+const IMMORTAL: &str = r#"[Event "London"]
+[Site "London ENG"]
+[Date "1851.06.21"]
+[Round "?"]
+[White "Adolf Anderssen"]
+[Black "Lionel Kieseritzky"]
+[Result "1-0"]
+[ECO "C33"]
+
+1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5 8. Nh4
+Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5 12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15. Bxf4
+Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6 Bxg1 19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+ Kd8
+22. Qf6+ Nxf6 23. Be7# 1-0
+"#;
+
+#[test]
+fn read_game() {
+    let pgn: Pgn = IMMORTAL.parse().unwrap();
+
+    assert_eq!(pgn.tag("White"), Some("Adolf Anderssen"));
+    assert_eq!(pgn.tag("ECO"), Some("C33"));
+    assert_eq!(pgn.result, GameResult::WhiteWins);
+    assert_eq!(pgn.mainline.moves.len(), 45);
+    assert_eq!(pgn.mainline.moves[44].san(), "Be7#");
+    assert!(pgn.end().legals().is_empty());
+}
+
+#[test]
+fn read_comments_nags_and_variations() {
+    let pgn: Pgn = "{Start} 1. e4 $1 {Best by test} (1. d4 d5 (1... Nf6) 2. c4) 1... e5?! 2. Nf3 \
+                    ; to the end of the line\n*"
+        .parse()
+        .unwrap();
+
+    let moves = &pgn.mainline.moves;
+    assert_eq!(pgn.mainline.comment.as_deref(), Some("Start"));
+    assert_eq!(moves.len(), 3);
+    assert_eq!(moves[0].nags, [1]);
+    assert_eq!(moves[0].comment.as_deref(), Some("Best by test"));
+    assert_eq!(moves[1].nags, [6]);
+    assert_eq!(moves[2].comment.as_deref(), Some("to the end of the line"));
+    assert_eq!(pgn.result, GameResult::Unknown);
+
+    let variation = &moves[0].variations[0];
+    let sans = variation
+        .moves
+        .iter()
+        .map(|mv| mv.san())
+        .collect::<Vec<_>>();
+    assert_eq!(sans, ["d4", "d5", "c4"]);
+    assert_eq!(variation.moves[1].variations[0].moves[0].san(), "Nf6");
+}
+
+#[test]
+fn write_game() {
+    let pgn: Pgn = IMMORTAL.parse().unwrap();
+    let written = pgn.to_string();
+
+    assert!(written.starts_with("[Event \"London\"]\n[Site \"London ENG\"]\n"));
+    assert!(written.contains("[Result \"1-0\"]\n[ECO \"C33\"]\n\n1. e4 e5 2. f4"));
+    assert!(written.ends_with("23. Be7# 1-0\n"));
+    assert!(written.lines().all(|line| line.len() < 80), "{written}");
+    assert_eq!(written.parse::<Pgn>().unwrap(), pgn);
+
+    let pgn: Pgn = "1. e4 {A \"good\" move} (1. d4 d5) 1... e5 (1... c5 2. Nf3) 2. Nf3 *"
+        .parse()
+        .unwrap();
+    let written = pgn.to_string();
+    assert!(
+        written
+            .ends_with("\n1. e4 {A \"good\" move} (1. d4 d5) 1... e5 (1... c5 2. Nf3) 2. Nf3 *\n"),
+        "{written}"
+    );
+    assert!(written.starts_with("[Event \"?\"]\n"));
+    assert_eq!(written.parse::<Pgn>().unwrap().mainline, pgn.mainline);
+}
+
+#[test]
+fn write_setup() {
+    let fen = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    let mut pgn = Pgn::new(fen.parse().unwrap());
+    pgn.set_tag("Annotator", r"C:\Users");
+    assert!(pgn.push("a7a8q".parse().unwrap()));
+    assert!(!pgn.push("a7a8q".parse().unwrap()));
+    pgn.result = GameResult::Draw;
+
+    let written = pgn.to_string();
+    assert!(written.contains("[SetUp \"1\"]\n[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n"));
+    assert!(written.contains(r#"[Annotator "C:\\Users"]"#));
+    assert!(written.ends_with("\n1. a8=Q+ 1/2-1/2\n"));
+
+    let read: Pgn = written.parse().unwrap();
+    assert_eq!(read.start, pgn.start);
+    assert_eq!(read.tag("Annotator"), Some(r"C:\Users"));
+    assert_eq!(read.mainline, pgn.mainline);
+}
+
+#[test]
+fn read_database() {
+    let database = format!(
+        "{IMMORTAL}\n[Event \"Broken\"]\n\n1. e4 e5 2. Ke3 *\n\n\
+         [Event \"No result\"]\n1. d4\n\n% an escaped line\n[Event \"Last\"]\n1.e4 1...c5 0-1"
+    );
+
+    let games = PgnReader::new(database.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(games.len(), 4);
+
+    assert_eq!(games[0].as_ref().unwrap().mainline.moves.len(), 45);
+    assert!(matches!(
+        games[1],
+        Err(PgnError::IllegalMove {
+            line: 17,
+            error: ParseSanError::IllegalMove,
+            ..
+        })
+    ));
+
+    let no_result = games[2].as_ref().unwrap();
+    assert_eq!(no_result.tag("Event"), Some("No result"));
+    assert_eq!(no_result.result, GameResult::Unknown);
+    assert_eq!(no_result.mainline.moves.len(), 1);
+
+    let last = games[3].as_ref().unwrap();
+    assert_eq!(last.result, GameResult::BlackWins);
+    assert_eq!(last.mainline.moves.len(), 2);
+}
+
+/// Counts the mainline moves of games by a player, skipping everything else
+#[test]
+fn visitor_skips() {
+    struct Counter {
+        player: bool,
+        board: Board,
+        moves: usize,
+    }
+
+    impl Visitor for Counter {
+        type Output = usize;
+
+        fn begin_game(&mut self) {
+            self.player = false;
+            self.moves = 0;
+        }
+
+        fn tag(&mut self, name: &str, value: &str) {
+            self.player |= name == "White" && value == "Adolf Anderssen";
+        }
+
+        fn end_tags(&mut self, start: &Board) -> ControlFlow<()> {
+            self.board = *start;
+            if self.player {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        }
+
+        fn san(&mut self, san: &str) -> Result<(), ParseSanError> {
+            let mv = self.board.parse_san(san)?;
+            assert!(self.board.move_mut(mv));
+            self.moves += 1;
+            Ok(())
+        }
+
+        fn begin_variation(&mut self) -> ControlFlow<()> {
+            ControlFlow::Break(())
+        }
+
+        fn end_game(&mut self) -> usize {
+            self.moves
+        }
+    }
+
+    let database = format!("[White \"Someone\"]\n\n1. e4 Zz9 *\n\n{IMMORTAL}");
+    let mut reader = PgnReader::new(database.as_bytes());
+    let mut counter = Counter {
+        player: false,
+        board: Board::standard(),
+        moves: 0,
+    };
+
+    assert_eq!(reader.read_game(&mut counter).unwrap(), Some(0));
+    assert_eq!(reader.read_game(&mut counter).unwrap(), Some(45));
+    assert_eq!(reader.read_game(&mut counter).unwrap(), None);
+
+    let mut reader =
+        PgnReader::new("[White \"Adolf Anderssen\"]\n1. e4 (1. d4 (1. c4) Zz9) e5 *".as_bytes());
+    assert_eq!(reader.read_game(&mut counter).unwrap(), Some(2));
+}
@@ -0,0 +1,132 @@
+use chess_bitboard::PromotionPiece;
+use chess_movegen::{san::ParseSanError, Board, ChessMove};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn san(fen: &str, mv: &str) -> String {
+    san_promotion(fen, mv, None)
+}
+
+fn san_promotion(fen: &str, mv: &str, promotion: Option<PromotionPiece>) -> String {
+    let board: Board = fen.parse().unwrap();
+    let mv = ChessMove {
+        piece: promotion,
+        ..mv.parse().unwrap()
+    };
+    board.san(mv)
+}
+
+fn parse(fen: &str, san: &str) -> Result<String, ParseSanError> {
+    let board: Board = fen.parse().unwrap();
+    board.parse_san(san).map(|mv| mv.to_string())
+}
+
+#[test]
+fn san_pieces_and_pawns() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(san(start, "e2e4"), "e4");
+    assert_eq!(san(start, "g1f3"), "Nf3");
+
+    assert_eq!(san(KIWIPETE, "e5f7"), "Nxf7");
+    assert_eq!(san(KIWIPETE, "d5e6"), "dxe6");
+    assert_eq!(san(KIWIPETE, "e1g1"), "O-O");
+    assert_eq!(san(KIWIPETE, "e1c1"), "O-O-O");
+    assert_eq!(san(KIWIPETE, "e1f1"), "Kf1");
+}
+
+#[test]
+fn san_disambiguation() {
+    let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(san(fen, "b1d2"), "Nbd2");
+    assert_eq!(san(fen, "f1d2"), "Nfd2");
+    assert_eq!(san(fen, "b1c3"), "Nc3");
+
+    let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+    assert_eq!(san(fen, "a1a3"), "R1a3");
+    assert_eq!(san(fen, "a5a3"), "R5a3");
+
+    let fen = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+    assert_eq!(san(fen, "a1b2"), "Qa1b2");
+    assert_eq!(san(fen, "a3b2"), "Q3b2");
+    assert_eq!(san(fen, "c1b2"), "Qcb2");
+}
+
+#[test]
+fn san_special_moves() {
+    let fen = "1r2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(san(fen, "e5d6"), "exd6");
+    assert_eq!(
+        san_promotion(fen, "a7a8", Some(PromotionPiece::Queen)),
+        "a8=Q"
+    );
+    assert_eq!(
+        san_promotion(fen, "a7b8", Some(PromotionPiece::Knight)),
+        "axb8=N"
+    );
+
+    assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+}
+
+#[test]
+fn parse_san_lenient_forms() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    for san in ["Nf3", "Nf3+", "Nf3!?", "Ngf3", "Ng1f3", "N-f3", "Ng1-f3"] {
+        assert_eq!(parse(start, san).as_deref(), Ok("g1-f3"), "{san}");
+    }
+    assert_eq!(parse(start, "e4").as_deref(), Ok("e2-e4"));
+    assert_eq!(parse(start, "Pe4").as_deref(), Ok("e2-e4"));
+
+    for san in ["O-O", "0-0", "O-O+"] {
+        assert_eq!(parse(KIWIPETE, san).as_deref(), Ok("e1-g1"), "{san}");
+    }
+    assert_eq!(parse(KIWIPETE, "0-0-0").as_deref(), Ok("e1-c1"));
+    assert_eq!(parse(KIWIPETE, "Nf7").as_deref(), Ok("e5-f7"));
+    assert_eq!(parse(KIWIPETE, "dxe6").as_deref(), Ok("d5-e6"));
+    assert_eq!(parse(KIWIPETE, "de6").as_deref(), Ok("d5-e6"));
+
+    let fen = "1r2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(parse(fen, "exd6 e.p.").as_deref(), Ok("e5-d6"));
+    for san in ["a8=Q", "a8Q", "a8q", "a8=Q+"] {
+        assert_eq!(parse(fen, san).as_deref(), Ok("a7-a8Q"), "{san}");
+    }
+    assert_eq!(parse(fen, "axb8=N").as_deref(), Ok("a7-b8N"));
+}
+
+#[test]
+fn parse_san_errors() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(parse(start, "Nf4"), Err(ParseSanError::IllegalMove));
+    assert_eq!(parse(start, "O-O"), Err(ParseSanError::IllegalMove));
+    assert_eq!(parse(start, ""), Err(ParseSanError::Invalid));
+    assert_eq!(parse(start, "Zf3"), Err(ParseSanError::Invalid));
+    assert_eq!(parse(start, "Nz3"), Err(ParseSanError::Invalid));
+
+    let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(parse(fen, "Nd2"), Err(ParseSanError::AmbiguousMove));
+    // a promotion needs the piece
+    let fen = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(parse(fen, "a8"), Err(ParseSanError::IllegalMove));
+}
+
+#[test]
+fn san_round_trips() {
+    fn check(board: &Board, depth: usize) {
+        for mv in board.legals() {
+            let san = board.san(mv);
+            assert_eq!(board.parse_san(&san), Ok(mv), "{san} in {board}");
+
+            if depth > 0 {
+                check(&board.move_new(mv).unwrap(), depth - 1);
+            }
+        }
+    }
+
+    for fen in [
+        KIWIPETE,
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ] {
+        check(&fen.parse().unwrap(), 1);
+    }
+}
@@ -0,0 +1,102 @@
+use chess_bitboard::PromotionPiece;
+use chess_movegen::{Board, ChessMove};
+
+fn see(fen: &str, mv: &str) -> i32 {
+    see_promotion(fen, mv, None)
+}
+
+fn see_promotion(fen: &str, mv: &str, promotion: Option<PromotionPiece>) -> i32 {
+    let board: Board = fen.parse().unwrap();
+    let mv = ChessMove {
+        piece: promotion,
+        ..mv.parse().unwrap()
+    };
+    assert!(board.is_legal(mv), "{mv} is illegal in {fen}");
+
+    let see = board.see(mv);
+    assert!(board.see_ge(mv, see));
+    assert!(!board.see_ge(mv, see + 1));
+    see
+}
+
+#[test]
+fn see_undefended_pawn() {
+    assert_eq!(
+        see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+        100
+    );
+}
+
+#[test]
+fn see_long_exchange() {
+    assert_eq!(
+        see(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        ),
+        -200
+    );
+}
+
+#[test]
+fn see_even_trade() {
+    assert_eq!(see("4k3/3p4/2n5/8/B7/8/8/4K3 w - - 0 1", "a4c6"), 0);
+}
+
+#[test]
+fn see_x_rays() {
+    // the rook on d8 recaptures after the one on d7
+    assert_eq!(see("3r1k2/3r4/8/3p4/8/8/3R4/3QK3 w - - 0 1", "d2d5"), -400);
+    assert_eq!(see("5k2/3r4/8/3p4/8/8/3R4/3QK3 w - - 0 1", "d2d5"), 100);
+}
+
+#[test]
+fn see_en_passant() {
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    assert_eq!(see("4k3/2p5/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 0);
+    // the captured pawn no longer blocks the rook on d1 from recapturing
+    assert_eq!(see("3r1k2/8/8/3pP3/8/8/8/3RK3 w - d6 0 1", "e5d6"), 100);
+}
+
+#[test]
+fn see_promotions() {
+    let queen = Some(PromotionPiece::Queen);
+    assert_eq!(
+        see_promotion("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8", queen),
+        1300
+    );
+    assert_eq!(
+        see_promotion("rr2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8", queen),
+        400
+    );
+    assert_eq!(
+        see_promotion(
+            "rr2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+            "a7b8",
+            Some(PromotionPiece::Knight)
+        ),
+        400
+    );
+    // the pawn promotes to a queen when it recaptures
+    assert_eq!(see("4k3/8/8/8/8/1R6/2p5/4K3 w - - 0 1", "b3b1"), -1300);
+}
+
+#[test]
+fn see_kings() {
+    assert_eq!(see("4k3/3p4/8/8/8/8/8/3RK3 w - - 0 1", "d1d7"), -400);
+    // the king can't recapture a defended rook
+    assert_eq!(see("4k3/3p4/8/8/B7/8/8/3RK3 w - - 0 1", "d1d7"), 100);
+}
+
+#[test]
+fn see_quiet_moves() {
+    assert_eq!(see("4k3/8/8/3p4/8/N7/8/4K3 w - - 0 1", "a3c4"), -300);
+    assert_eq!(see("4k3/8/8/3p4/8/N7/8/4K3 w - - 0 1", "a3b5"), 0);
+    assert_eq!(
+        see(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "e1g1"
+        ),
+        0
+    );
+}
@@ -0,0 +1,116 @@
+use chess_bitboard::{Pos, PromotionPiece};
+use chess_movegen::{uci::ParseUciMoveError, Board, ChessMove};
+
+const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+fn parse(fen: &str, mv: &str) -> Result<String, ParseUciMoveError> {
+    let board: Board = fen.parse().unwrap();
+    board.parse_uci_move(mv).map(|mv| mv.uci().to_string())
+}
+
+#[test]
+fn parse_chess_moves() {
+    let mv = |s: &str| s.parse::<ChessMove>();
+
+    assert_eq!(
+        mv("e2e4"),
+        Ok(ChessMove {
+            source: Pos::E2,
+            dest: Pos::E4,
+            piece: None
+        })
+    );
+    assert_eq!(mv("e2-e4"), mv("e2e4"));
+    assert_eq!(
+        mv("e7e8q"),
+        Ok(ChessMove {
+            source: Pos::E7,
+            dest: Pos::E8,
+            piece: Some(PromotionPiece::Queen)
+        })
+    );
+    assert_eq!(mv("e7-e8Q"), mv("e7e8q"));
+    assert_eq!(mv("0000"), Ok(ChessMove::NULL));
+
+    for invalid in ["", "e2", "e2e", "e2e9", "e7e8k", "e7e8qq", "e2--e4"] {
+        assert_eq!(mv(invalid), Err(()), "{invalid}");
+    }
+}
+
+#[test]
+fn uci_notation_round_trips() {
+    for s in ["e2e4", "a7b8n", "h2h1r", "0000"] {
+        let mv: ChessMove = s.parse().unwrap();
+        assert_eq!(mv.uci().to_string(), s);
+        // `Display` is also accepted
+        assert_eq!(mv.to_string().parse(), Ok(mv));
+    }
+
+    fn check(board: &Board, depth: usize) {
+        for mv in board.legals() {
+            let uci = mv.uci().to_string();
+            assert_eq!(board.parse_uci_move(&uci), Ok(mv), "{uci} in {board}");
+
+            if depth > 0 {
+                check(&board.move_new(mv).unwrap(), depth - 1);
+            }
+        }
+    }
+
+    check(&KIWIPETE.parse().unwrap(), 1);
+    check(
+        &"r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"
+            .parse()
+            .unwrap(),
+        1,
+    );
+}
+
+#[test]
+fn parse_castling() {
+    assert_eq!(parse(KIWIPETE, "e1g1").as_deref(), Ok("e1g1"));
+    assert_eq!(parse(KIWIPETE, "e1h1").as_deref(), Ok("e1g1"));
+    assert_eq!(parse(KIWIPETE, "e1c1").as_deref(), Ok("e1c1"));
+    assert_eq!(parse(KIWIPETE, "e1a1").as_deref(), Ok("e1c1"));
+
+    // without castling rights the king can't take its rook
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Qkq - 0 1";
+    assert_eq!(parse(fen, "e1h1"), Err(ParseUciMoveError::IllegalMove));
+    assert_eq!(parse(fen, "e1a1").as_deref(), Ok("e1c1"));
+
+    // the king only castles with the rook it takes
+    let fen = "4k3/8/8/8/8/8/8/RR2K2R w KB - 0 1";
+    assert_eq!(parse(fen, "e1a1"), Err(ParseUciMoveError::IllegalMove));
+    assert_eq!(parse(fen, "e1b1").as_deref(), Ok("e1c1"));
+
+    // with the king off the e-file castling is written as the king taking its own rook, which
+    // may be left of the center on the king's side
+    let fen = "4k3/8/8/8/8/8/8/RKR5 w CA - 0 1";
+    assert_eq!(parse(fen, "b1c1").as_deref(), Ok("b1c1"));
+    // the other rook is on c1, where the king would castle to
+    assert_eq!(parse(fen, "b1a1"), Err(ParseUciMoveError::IllegalMove));
+    let board: Board = fen.parse().unwrap();
+    assert_eq!(board.san(board.parse_uci_move("b1c1").unwrap()), "O-O");
+
+    for index in [0, 518, 959] {
+        let board = Board::chess960(index).unwrap();
+        for mv in board.legals() {
+            let uci = mv.uci().to_string();
+            assert_eq!(board.parse_uci_move(&uci), Ok(mv), "{uci} in {board}");
+        }
+    }
+}
+
+#[test]
+fn parse_uci_move_errors() {
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(parse(start, "e2e5"), Err(ParseUciMoveError::IllegalMove));
+    assert_eq!(parse(start, "0000"), Err(ParseUciMoveError::IllegalMove));
+    assert_eq!(parse(start, "e2"), Err(ParseUciMoveError::Invalid));
+
+    // a promotion needs the piece, and other moves can't have one
+    let fen = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(parse(fen, "a7a8"), Err(ParseUciMoveError::IllegalMove));
+    assert_eq!(parse(fen, "a7a8n").as_deref(), Ok("a7a8n"));
+    assert_eq!(parse(fen, "e1e2q"), Err(ParseUciMoveError::IllegalMove));
+}
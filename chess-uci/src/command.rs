@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use chess_movegen::{fen::ParseFenError, Board, ChessMove};
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Uci,
+    Debug,
+    IsReady,
+    SetOption { name: String, value: Option<String> },
+    UciNewGame,
+    Position { start: Board, moves: Vec<ChessMove> },
+    Go(Go),
+    Stop,
+    PonderHit,
+    Quit,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Go {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u16>,
+    pub movetime: Option<Duration>,
+    pub depth: Option<u16>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u16>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCommandError {
+    Empty,
+    UnknownCommand(String),
+    MissingValue(&'static str),
+    InvalidValue { name: &'static str, value: String },
+    InvalidFen(ParseFenError),
+    IllegalMove(String),
+}
+
+impl core::fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseCommandError::Empty => write!(f, "empty command"),
+            ParseCommandError::UnknownCommand(cmd) => write!(f, "unknown command {cmd}"),
+            ParseCommandError::MissingValue(name) => write!(f, "missing value for {name}"),
+            ParseCommandError::InvalidValue { name, value } => {
+                write!(f, "invalid value {value} for {name}")
+            }
+            ParseCommandError::InvalidFen(err) => write!(f, "invalid fen: {err}"),
+            ParseCommandError::IllegalMove(mv) => write!(f, "illegal move {mv}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseCommandError {}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, ParseCommandError> {
+        let mut tokens = line.split_ascii_whitespace();
+
+        let command = tokens.next().ok_or(ParseCommandError::Empty)?;
+
+        Ok(match command {
+            "uci" => Command::Uci,
+            "debug" => Command::Debug,
+            "isready" => Command::IsReady,
+            "setoption" => parse_setoption(tokens)?,
+            "ucinewgame" => Command::UciNewGame,
+            "position" => parse_position(tokens)?,
+            "go" => Command::Go(parse_go(tokens)?),
+            "stop" => Command::Stop,
+            "ponderhit" => Command::PonderHit,
+            "quit" => Command::Quit,
+            _ => return Err(ParseCommandError::UnknownCommand(command.to_owned())),
+        })
+    }
+}
+
+fn parse_setoption<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+) -> Result<Command, ParseCommandError> {
+    if tokens.next() != Some("name") {
+        return Err(ParseCommandError::MissingValue("name"));
+    }
+
+    // both the name and the value may contain spaces
+    let mut name = Vec::new();
+    let mut value = None::<Vec<&str>>;
+
+    for token in tokens {
+        match &mut value {
+            Some(value) => value.push(token),
+            None if token == "value" => value = Some(Vec::new()),
+            None => name.push(token),
+        }
+    }
+
+    if name.is_empty() {
+        return Err(ParseCommandError::MissingValue("name"));
+    }
+
+    Ok(Command::SetOption {
+        name: name.join(" "),
+        value: value.map(|value| value.join(" ")),
+    })
+}
+
+fn parse_position<'a>(
+    mut tokens: impl Iterator<Item = &'a str>,
+) -> Result<Command, ParseCommandError> {
+    let start = match tokens.next() {
+        Some("startpos") => {
+            if let Some(token) = tokens.next() {
+                if token != "moves" {
+                    return Err(ParseCommandError::MissingValue("moves"));
+                }
+            }
+
+            Board::standard()
+        }
+        Some("fen") => {
+            let mut fen = Vec::new();
+
+            for token in tokens.by_ref() {
+                if token == "moves" {
+                    break;
+                }
+
+                fen.push(token);
+            }
+
+            // some guis leave off the move clocks
+            if fen.len() == 4 {
+                fen.extend(["0", "1"]);
+            }
+
+            fen.join(" ")
+                .parse::<Board>()
+                .map_err(ParseCommandError::InvalidFen)?
+        }
+        _ => return Err(ParseCommandError::MissingValue("position")),
+    };
+
+    let mut board = start;
+    let mut moves = Vec::new();
+
+    for token in tokens {
+        let mv = board
+            .parse_uci_move(token)
+            .map_err(|_| ParseCommandError::IllegalMove(token.to_owned()))?;
+
+        unsafe { board.move_unchecked_mut(mv) };
+        moves.push(mv);
+    }
+
+    Ok(Command::Position { start, moves })
+}
+
+fn parse_go<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Result<Go, ParseCommandError> {
+    fn value<'a, T: std::str::FromStr>(
+        tokens: &mut impl Iterator<Item = &'a str>,
+        name: &'static str,
+    ) -> Result<T, ParseCommandError> {
+        let value = tokens.next().ok_or(ParseCommandError::MissingValue(name))?;
+
+        value.parse().map_err(|_| ParseCommandError::InvalidValue {
+            name,
+            value: value.to_owned(),
+        })
+    }
+
+    fn millis<'a>(
+        tokens: &mut impl Iterator<Item = &'a str>,
+        name: &'static str,
+    ) -> Result<Duration, ParseCommandError> {
+        // guis may send negative times when the clock has run out
+        value::<i64>(tokens, name).map(|ms| Duration::from_millis(ms.max(0) as u64))
+    }
+
+    let mut go = Go::default();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "wtime" => go.wtime = Some(millis(&mut tokens, "wtime")?),
+            "btime" => go.btime = Some(millis(&mut tokens, "btime")?),
+            "winc" => go.winc = Some(millis(&mut tokens, "winc")?),
+            "binc" => go.binc = Some(millis(&mut tokens, "binc")?),
+            "movetime" => go.movetime = Some(millis(&mut tokens, "movetime")?),
+            "movestogo" => go.movestogo = Some(value(&mut tokens, "movestogo")?),
+            "depth" => go.depth = Some(value(&mut tokens, "depth")?),
+            "nodes" => go.nodes = Some(value(&mut tokens, "nodes")?),
+            "infinite" => go.infinite = true,
+            "ponder" => go.ponder = true,
+            "mate" => go.mate = Some(value(&mut tokens, "mate")?),
+            "searchmoves" => break,
+            _ => {
+                return Err(ParseCommandError::InvalidValue {
+                    name: "go",
+                    value: token.to_owned(),
+                })
+            }
+        }
+    }
+
+    Ok(go)
+}
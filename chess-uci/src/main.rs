@@ -0,0 +1,294 @@
+use std::{
+    io::BufRead,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use chess_bitboard::Color;
+use chess_engine::{
+    Clock, Engine, Network, Score, SearchInfo, SearchLimits, ThreeFold, TimeManager, Timeout,
+};
+use chess_movegen::{Board, ChessMove, Game};
+
+mod command;
+
+use command::{Command, Go};
+
+const DEFAULT_HASH_MB: usize = 16;
+const MAX_HASH_MB: usize = 4096;
+const MAX_THREADS: usize = 256;
+
+/// Check options which toggle the fields of [`chess_engine::SearchConfig`]
+const SELECTIVE_OPTIONS: [&str; 4] = [
+    "NullMove",
+    "LateMoveReductions",
+    "FutilityPruning",
+    "CheckExtensions",
+];
+
+/// Time kept in reserve to account for communication overhead with the gui
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+struct State {
+    /// The engine is moved to the search thread while searching
+    engine: Option<Engine>,
+    search: Option<Search>,
+    start: Board,
+    moves: Vec<ChessMove>,
+}
+
+struct Search {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Engine>,
+}
+
+#[derive(Clone, Copy)]
+struct UciTimeout<'a> {
+    stop: &'a AtomicBool,
+    clock: Option<&'a TimeManager>,
+}
+
+impl Timeout for UciTimeout<'_> {
+    #[inline]
+    fn is_complete(&self) -> bool {
+        self.stop.load(Ordering::Relaxed) || self.clock.is_some_and(|clock| clock.is_complete())
+    }
+
+    #[inline]
+    fn stop_after_iteration(&self, depth: u16, best_move: Option<ChessMove>, score: Score) -> bool {
+        self.stop.load(Ordering::Relaxed)
+            || self
+                .clock
+                .is_some_and(|clock| clock.stop_after_iteration(depth, best_move, score))
+    }
+}
+
+fn main() {
+    let mut state = State {
+        engine: Some(Engine::with_hash_size(DEFAULT_HASH_MB)),
+        search: None,
+        start: Board::standard(),
+        moves: Vec::new(),
+    };
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+
+        let command = match Command::parse(&line) {
+            Ok(command) => command,
+            Err(command::ParseCommandError::Empty) => continue,
+            Err(err) => {
+                println!("info string {err}");
+                continue;
+            }
+        };
+
+        match command {
+            Command::Uci => {
+                println!("id name chess {}", env!("CARGO_PKG_VERSION"));
+                println!("id author RustyYato");
+                println!(
+                    "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                );
+                println!("option name Clear Hash type button");
+                println!("option name Threads type spin default 1 min 1 max {MAX_THREADS}");
+                println!("option name Positional type check default false");
+                println!("option name EvalFile type string default <empty>");
+                for name in SELECTIVE_OPTIONS {
+                    println!("option name {name} type check default true");
+                }
+                println!("uciok");
+            }
+            Command::Debug | Command::PonderHit => (),
+            Command::IsReady => println!("readyok"),
+            Command::SetOption { name, value } => {
+                let engine = state.engine();
+                match (name.to_ascii_lowercase().as_str(), value.as_deref()) {
+                    ("hash", Some(value)) => match value.parse::<usize>() {
+                        Ok(size) => engine.set_hash_size(size.clamp(1, MAX_HASH_MB)),
+                        Err(_) => println!("info string invalid hash size {value}"),
+                    },
+                    ("clear hash", _) => engine.clear_hash(),
+                    ("threads", Some(value)) => match value.parse::<usize>() {
+                        Ok(threads) => engine.threads = threads.clamp(1, MAX_THREADS),
+                        Err(_) => println!("info string invalid thread count {value}"),
+                    },
+                    ("positional", Some(value)) => engine.positional = value == "true",
+                    // without a network the classical evaluation is used
+                    ("evalfile", None | Some("" | "<empty>")) => engine.set_network(None),
+                    ("evalfile", Some(path)) => match Network::load(path) {
+                        Ok(network) => engine.set_network(Some(network)),
+                        Err(err) => println!("info string could not load {path}: {err}"),
+                    },
+                    ("nullmove", Some(value)) => engine.config.null_move_pruning = value == "true",
+                    ("latemovereductions", Some(value)) => {
+                        engine.config.late_move_reductions = value == "true"
+                    }
+                    ("futilitypruning", Some(value)) => {
+                        engine.config.futility_pruning = value == "true"
+                    }
+                    ("checkextensions", Some(value)) => {
+                        engine.config.check_extensions = value == "true"
+                    }
+                    _ => println!("info string unknown option {name}"),
+                }
+            }
+            Command::UciNewGame => {
+                state.engine().clear_hash();
+                state.start = Board::standard();
+                state.moves.clear();
+            }
+            Command::Position { start, moves } => {
+                state.stop();
+                state.start = start;
+                state.moves = moves;
+            }
+            Command::Go(go) => state.go(go),
+            Command::Stop => state.stop(),
+            Command::Quit => break,
+        }
+    }
+
+    state.stop();
+}
+
+impl State {
+    fn engine(&mut self) -> &mut Engine {
+        self.stop();
+        self.engine.as_mut().unwrap()
+    }
+
+    /// Stop the current search, if any, and wait for it to report its best move
+    fn stop(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            search.handle.thread().unpark();
+            self.engine = Some(search.handle.join().expect("search thread panicked"));
+        }
+    }
+
+    fn go(&mut self, go: Go) {
+        self.stop();
+        let mut engine = self.engine.take().unwrap();
+
+        let mut game = Game::new(self.start);
+        for &mv in &self.moves {
+            assert!(game.push(mv));
+        }
+
+        let board = *game.board();
+        let three_fold = ThreeFold::from(&game);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let turn = board.turn();
+        let clock = clock(&go, turn).map(|clock| TimeManager::new(clock, turn));
+
+        let handle = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                let limits = SearchLimits {
+                    depth: go.depth,
+                    nodes: go.nodes,
+                    move_time: go.movetime.map(|time| {
+                        time.saturating_sub(MOVE_OVERHEAD)
+                            .max(Duration::from_millis(1))
+                    }),
+                    mate: go.mate,
+                    infinite: go.infinite || go.ponder,
+                };
+
+                let timeout = UciTimeout {
+                    stop: &stop,
+                    clock: clock.as_ref(),
+                };
+
+                let (mv, _) = engine.search_limited(
+                    &board,
+                    &three_fold,
+                    limits,
+                    timeout,
+                    |info: &SearchInfo| print_info(info, board.turn()),
+                );
+
+                // the search may only finish early if the gui asked for it
+                if go.infinite || go.ponder {
+                    while !stop.load(Ordering::Relaxed) {
+                        std::thread::park();
+                    }
+                }
+
+                // the search may time out before completing a single iteration
+                match mv.or_else(|| board.legals().next()) {
+                    Some(mv) => println!("bestmove {}", mv.uci()),
+                    None => println!("bestmove 0000"),
+                }
+
+                engine
+            }
+        });
+
+        self.search = Some(Search { stop, handle });
+    }
+}
+
+/// The clock of the side to move, fixed move times are handled by [`SearchLimits::move_time`]
+fn clock(go: &Go, turn: Color) -> Option<Clock> {
+    if go.infinite || go.ponder || go.movetime.is_some() {
+        return None;
+    }
+
+    let (time, increment) = match turn {
+        Color::White => (go.wtime?, go.winc.unwrap_or_default()),
+        Color::Black => (go.btime?, go.binc.unwrap_or_default()),
+    };
+
+    Some(Clock {
+        remaining: time
+            .saturating_sub(MOVE_OVERHEAD)
+            .max(Duration::from_millis(1)),
+        increment,
+        moves_to_go: go.movestogo,
+    })
+}
+
+fn print_info(info: &SearchInfo, turn: Color) {
+    let mut line = format!(
+        "info depth {} seldepth {} score {} nodes {} nps {} time {} hashfull {}",
+        info.depth,
+        info.seldepth,
+        uci_score(info.score, turn),
+        info.nodes,
+        info.nps(),
+        info.elapsed.as_millis(),
+        info.hashfull,
+    );
+
+    if !info.pv.is_empty() {
+        line.push_str(" pv");
+        for &mv in info.pv {
+            line.push_str(&format!(" {}", mv.uci()));
+        }
+    }
+
+    println!("{line}");
+}
+
+/// Scores in uci are relative to the side to move, in centipawns or moves until mate
+fn uci_score(score: Score, turn: Color) -> String {
+    let sign = match turn {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+
+    match score {
+        Score::Raw(cp) => format!("cp {}", cp * sign),
+        Score::WhiteMateIn(plies) => format!("mate {}", (i32::from(plies) + 1) / 2 * sign),
+        Score::BlackMateIn(plies) => format!("mate {}", -(i32::from(plies) + 1) / 2 * sign),
+        Score::Max => format!("cp {}", i32::from(i16::MAX) * sign),
+        Score::Min => format!("cp {}", -i32::from(i16::MAX) * sign),
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_chess-uci"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        Self {
+            child,
+            stdin,
+            stdout,
+        }
+    }
+
+    fn send(&mut self, command: &str) {
+        writeln!(self.stdin, "{command}").unwrap();
+    }
+
+    /// Read lines until one starts with `prefix`, returning all lines read
+    fn read_until(&mut self, prefix: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = String::new();
+            assert_ne!(self.stdout.read_line(&mut line).unwrap(), 0, "{lines:#?}");
+            let line = line.trim_end().to_owned();
+            let done = line.starts_with(prefix);
+            lines.push(line);
+
+            if done {
+                return lines;
+            }
+        }
+    }
+
+    fn best_move(&mut self) -> String {
+        let lines = self.read_until("bestmove");
+        let last = lines.last().unwrap();
+        last.split_ascii_whitespace().nth(1).unwrap().to_owned()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn handshake() {
+    let mut session = Session::new();
+
+    session.send("uci");
+    let lines = session.read_until("uciok");
+    assert!(lines[0].starts_with("id name"));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("option name Hash")));
+
+    session.send("setoption name Hash value 1");
+    session.send("setoption name Clear Hash");
+    session.send("setoption name Threads value 2");
+    session.send("isready");
+    assert_eq!(session.read_until("readyok"), ["readyok"]);
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let mut session = Session::new();
+
+    session.send("ucinewgame");
+    session.send("setoption name Threads value 2");
+    session.send("position fen 6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1");
+    session.send("go depth 3");
+    let lines = session.read_until("bestmove");
+
+    assert_eq!(lines.last().unwrap(), "bestmove d1d8");
+    assert!(lines.iter().any(|line| line.contains("score mate 1")));
+}
+
+#[test]
+fn position_with_moves() {
+    let mut session = Session::new();
+
+    // scholar's mate is available after these moves
+    session.send("position startpos moves e2e4 e7e5 f1c4 b8c6 d1h5 g8f6");
+    session.send("go depth 2");
+    assert_eq!(session.best_move(), "h5f7");
+
+    // promotions use a lower case suffix, and black's score is from its perspective
+    session.send("position fen 8/8/8/8/8/6k1/p7/6K1 b - - 0 1 moves g3h3 g1h1");
+    session.send("go depth 1");
+    let lines = session.read_until("bestmove");
+    assert!(lines.last().unwrap().starts_with("bestmove a2a1"));
+}
+
+#[test]
+fn stop_infinite_search() {
+    let mut session = Session::new();
+
+    session.send("position startpos");
+    session.send("go infinite");
+    session.send("isready");
+    session.read_until("readyok");
+    session.send("stop");
+
+    let mv = session.best_move();
+    assert_ne!(mv, "0000");
+}
+
+#[test]
+fn clock_and_node_limits() {
+    let mut session = Session::new();
+
+    session.send("position startpos moves d2d4");
+    session.send("go wtime 1000 btime 1000 winc 10 binc 10");
+    session.best_move();
+
+    session.send("go movetime 50");
+    session.best_move();
+
+    session.send("go nodes 1000");
+    session.best_move();
+
+    session.send("position fen 6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1");
+    session.send("go mate 1");
+    assert_eq!(session.best_move(), "d1d8");
+
+    session.send("position fen 8/8/8/8/8/8/8/8 w - - 0 1");
+    session.send("isready");
+    let lines = session.read_until("readyok");
+    assert!(lines[0].starts_with("info string invalid fen"));
+}
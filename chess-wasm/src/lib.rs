@@ -11,13 +11,13 @@ pub struct ChessEngine {
 
 #[wasm_bindgen]
 pub struct ChessGame {
-    history: chess_engine::ThreeFold,
-    board: chess_movegen::Board,
+    game: chess_movegen::Game,
 }
 
 #[wasm_bindgen]
 pub struct EngineChessMove {
     chess_move: Option<ChessMove>,
+    #[allow(dead_code)]
     score: chess_engine::Score,
 }
 
@@ -35,30 +35,93 @@ impl ChessEngine {
             .unwrap_or(Duration::from_secs(5));
 
         let (chess_move, score) = self.engine.search(
-            &game.board,
-            &game.history,
+            game.game.board(),
+            &chess_engine::ThreeFold::from(&game.game),
             chess_engine::DurationTimeout::new(timeout),
         );
 
         Ok(EngineChessMove { chess_move, score })
     }
+
+    /// Search for a move with `remaining` time left on the clock of the side to move,
+    /// and `increment` added after every move
+    pub fn search_with_clock(
+        &mut self,
+        game: &ChessGame,
+        remaining: String,
+        increment: Option<String>,
+        moves_to_go: Option<u16>,
+    ) -> Result<EngineChessMove, JsError> {
+        let parse = |duration: &str| {
+            parse_duration::parse(duration).map_err(|err| JsError::new(&err.to_string()))
+        };
+
+        let clock = chess_engine::Clock {
+            remaining: parse(&remaining)?,
+            increment: increment
+                .as_deref()
+                .map(parse)
+                .transpose()?
+                .unwrap_or_default(),
+            moves_to_go,
+        };
+
+        let board = game.game.board();
+        let time = chess_engine::TimeManager::new(clock, board.turn());
+        let history = chess_engine::ThreeFold::from(&game.game);
+        let (chess_move, score) = self.engine.search(board, &history, &time);
+
+        Ok(EngineChessMove { chess_move, score })
+    }
 }
 
 impl ChessGame {
     pub fn get(&self, file: u8, rank: u8) -> Result<u8, JsError> {
         let file = File::from_u8(file).ok_or_else(|| JsError::new("Invalid file"))?;
         let rank = Rank::from_u8(rank).ok_or_else(|| JsError::new("Invalid rank"))?;
-        match self.board.raw().get(Pos::new(file, rank)) {
+        match self.game.board().raw().get(Pos::new(file, rank)) {
             Some((color, piece)) => Ok((piece as u8 + 1) << 1 | color as u8),
             None => Ok(0),
         }
     }
 }
 
+#[wasm_bindgen]
+impl ChessGame {
+    /// Plays `mv`, which is written in long algebraic notation like `e2e4` or `e7e8q`
+    pub fn make_move(&mut self, mv: &str) -> Result<(), JsError> {
+        let mv = self
+            .game
+            .board()
+            .parse_uci_move(mv)
+            .map_err(|err| JsError::new(&err.to_string()))?;
+
+        assert!(self.game.push(mv));
+        Ok(())
+    }
+
+    /// Takes back the last move, and returns it in long algebraic notation
+    pub fn undo(&mut self) -> Option<String> {
+        self.game.pop().map(|mv| mv.uci().to_string())
+    }
+
+    /// How the game ended, like `draw by threefold repetition`, or nothing if it is still going
+    pub fn outcome(&self) -> Option<String> {
+        self.game.outcome().map(|outcome| outcome.to_string())
+    }
+
+    /// The game so far in PGN
+    pub fn pgn(&self) -> String {
+        self.game.to_pgn().to_string()
+    }
+}
+
 #[wasm_bindgen]
 impl EngineChessMove {
+    /// The move in long algebraic notation like `e2e4` or `e7e8q`, the same format
+    /// [`ChessGame::make_move`] takes
     pub fn chess_move(&self) -> Option<String> {
-        self.chess_move.map(|mv| mv.to_string())
+        self.chess_move.map(|mv| mv.uci().to_string())
     }
 }
 
@@ -84,8 +147,7 @@ pub fn new_engine() -> ChessEngine {
 #[wasm_bindgen]
 pub fn new_game() -> ChessGame {
     ChessGame {
-        history: chess_engine::ThreeFold::new(),
-        board: chess_movegen::Board::standard(),
+        game: chess_movegen::Game::default(),
     }
 }
 
@@ -95,7 +157,6 @@ pub fn new_game_from_fen(s: &str) -> Result<ChessGame, JsError> {
         .parse::<chess_movegen::Board>()
         .map_err(|err| JsError::new(&format!("{err:?}")))?;
     Ok(ChessGame {
-        history: chess_engine::ThreeFold::new(),
-        board,
+        game: chess_movegen::Game::new(board),
     })
 }